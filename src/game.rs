@@ -8,6 +8,7 @@ pub mod audio;
 mod biology;
 mod cells;
 mod movement;
+pub mod puzzle;
 pub mod spawn;
 
 pub(super) fn plugin(app: &mut App) {
@@ -19,5 +20,6 @@ pub(super) fn plugin(app: &mut App) {
         spawn::plugin,
         cells::plugin,
         biology::plugin,
+        puzzle::plugin,
     ));
 }
@@ -4,6 +4,10 @@ use bevy::{
     utils::HashMap,
 };
 
+use serde::Deserialize;
+
+use crate::game::{biology::codon_table::CodonTable, puzzle::level::LevelPack};
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<ImageKey>>();
     app.init_resource::<HandleMap<ImageKey>>();
@@ -16,6 +20,12 @@ pub(super) fn plugin(app: &mut App) {
 
     app.register_type::<HandleMap<SoundtrackKey>>();
     app.init_resource::<HandleMap<SoundtrackKey>>();
+
+    app.register_type::<HandleMap<CodonTableKey>>();
+    app.init_resource::<HandleMap<CodonTableKey>>();
+
+    app.register_type::<HandleMap<LevelPackKey>>();
+    app.init_resource::<HandleMap<LevelPackKey>>();
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect, Debug)]
@@ -332,7 +342,8 @@ impl FromWorld for HandleMap<SfxKey> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+/// Level files name these, see [`crate::game::puzzle::level`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect, Debug, Deserialize)]
 pub enum SoundtrackKey {
     Credits,
     Gameplay,
@@ -359,6 +370,57 @@ impl FromWorld for HandleMap<SoundtrackKey> {
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect, Debug)]
+pub enum CodonTableKey {
+    TokiPona,
+    Standard,
+}
+
+impl CodonTableKey {
+    pub const ALL: [CodonTableKey; 2] = [CodonTableKey::TokiPona, CodonTableKey::Standard];
+}
+
+impl AssetKey for CodonTableKey {
+    type Asset = CodonTable;
+}
+
+impl FromWorld for HandleMap<CodonTableKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [
+            (
+                CodonTableKey::TokiPona,
+                asset_server.load("codes/toki_pona.codons.ron"),
+            ),
+            (
+                CodonTableKey::Standard,
+                asset_server.load("codes/standard.codons.ron"),
+            ),
+        ]
+        .into()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect, Debug)]
+pub enum LevelPackKey {
+    Campaign,
+}
+
+impl AssetKey for LevelPackKey {
+    type Asset = LevelPack;
+}
+
+impl FromWorld for HandleMap<LevelPackKey> {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        [(
+            LevelPackKey::Campaign,
+            asset_server.load("levels/campaign.levels.ron"),
+        )]
+        .into()
+    }
+}
+
 pub trait AssetKey: Sized {
     type Asset: Asset;
 }
@@ -1,25 +1,73 @@
+pub mod alignment;
+pub mod ambiguity;
+pub mod analysis;
+pub mod codon_table;
+pub mod dna;
+pub mod editor;
+pub mod fasta;
+pub mod folding;
+pub mod history;
+pub mod lifecycle;
+pub mod mutation;
+pub mod orf;
+pub mod packed;
+pub mod parse;
+pub mod protein;
+pub mod reverse_translation;
+pub mod ribosome;
+pub mod splicing;
+pub mod storage;
 pub mod translation;
-use crate::game::cells::text::spawn_acid;
+pub mod trna;
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
-use translation::NucleobaseRnaToAminoChar;
+use codon_table::ActiveCodonTable;
+use editor::EditorCursor;
+use history::{Edit, EditHistory, HistorySettings, Step};
+use translation::{NucleobaseRnaToAminoChar, Product};
 
 pub const CODON_TABLE: NucleobaseRnaToAminoChar = NucleobaseRnaToAminoChar::default();
 
 pub fn plugin(app: &mut App) {
-    app.init_resource::<CodonState>();
-    app.init_resource::<Mrna>();
+    app.add_plugins((
+        analysis::plugin,
+        codon_table::plugin,
+        editor::plugin,
+        fasta::plugin,
+        folding::plugin,
+        history::plugin,
+        lifecycle::plugin,
+        ribosome::plugin,
+        storage::plugin,
+    ));
     app.init_resource::<LogTextRes>();
+    app.init_resource::<AllowedBases>();
     app.add_systems(Startup, spawn_log);
-    app.add_systems(Update, (build_codon, update_log));
+    app.add_systems(
+        Update,
+        (
+            select_mrna,
+            build_codon,
+            history::edit_history,
+            editor::edit_sequence,
+            update_log,
+        )
+            .chain(),
+    );
 }
 
-#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+/// The bases typed since the last whole codon, kept next to the [`Mrna`] they will join.
+/// A ring buffer, so taking a codon off the front doesn't shift the rest.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
 pub struct CodonState {
-    bases: Vec<NucleobaseRna>,
+    bases: VecDeque<NucleobaseRna>,
 }
 impl Default for CodonState {
     fn default() -> Self {
-        CodonState { bases: Vec::new() }
+        CodonState {
+            bases: VecDeque::new(),
+        }
     }
 }
 impl CodonState {
@@ -27,16 +75,119 @@ impl CodonState {
         if self.bases.len() < 3 {
             return None;
         }
-        Some(Codon::from_bases((
-            self.bases.remove(0),
-            self.bases.remove(0),
-            self.bases.remove(0),
-        )))
+        let mut next = || self.bases.pop_front().unwrap();
+        Some(Codon::from_bases((next(), next(), next())))
     }
     pub fn push(&mut self, base: NucleobaseRna) {
-        self.bases.push(base);
+        self.bases.push_back(base);
+    }
+}
+/// Triggered for each product finished on an mRNA, when a stop codon closes an open reading.
+/// That is usually a typed stop, but editing, pasting, mutating or redoing can finish one too.
+#[derive(Event, Debug, Clone)]
+pub struct ProductReleased {
+    /// The mRNA entity it was read from.
+    pub entity: Entity,
+    /// The message as it was when the product was finished.
+    pub mrna: Mrna,
+    pub table: NucleobaseRnaToAminoChar,
+    pub product: Product,
+}
+
+/// The products `after` has that `before` didn't, the ones an edit finished.
+/// They are matched by text, so an edit that only shifts a product along doesn't count.
+pub fn new_products(before: &Mrna, after: &Mrna, table: &NucleobaseRnaToAminoChar) -> Vec<Product> {
+    let mut old: Vec<String> = before
+        .products(table)
+        .into_iter()
+        .map(|product| product.text)
+        .collect();
+    let mut new = Vec::new();
+    for product in after.products(table) {
+        match old.iter().position(|text| *text == product.text) {
+            Some(index) => {
+                old.swap_remove(index);
+            }
+            None => new.push(product),
+        }
+    }
+    new
+}
+
+/// Trigger [`ProductReleased`] for each of the [`new_products`], `true` if there were any.
+pub fn release_new_products(
+    commands: &mut Commands,
+    entity: Entity,
+    before: &Mrna,
+    after: &Mrna,
+    table: &NucleobaseRnaToAminoChar,
+) -> bool {
+    let products = new_products(before, after, table);
+    let released = !products.is_empty();
+    for product in products {
+        commands.trigger(ProductReleased {
+            entity,
+            mrna: after.clone(),
+            table: table.clone(),
+            product,
+        });
+    }
+    released
+}
+
+/// Marks the mRNA that the player's typing goes to, there is at most one.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SelectedMrna;
+
+/// What an mRNA was spawned with, like a level's start, so a degraded one can begin again.
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct StartMrna(pub Mrna);
+
+/// An mRNA entity, spawned as a child of the cell transcribing it.
+#[derive(Bundle)]
+pub struct MrnaBundle {
+    name: Name,
+    start: StartMrna,
+    mrna: Mrna,
+    codon_state: CodonState,
+    history: EditHistory,
+    cursor: EditorCursor,
+}
+
+impl Default for MrnaBundle {
+    fn default() -> Self {
+        Self::with_mrna(Mrna::default())
     }
 }
+
+impl MrnaBundle {
+    pub fn with_mrna(mrna: Mrna) -> Self {
+        Self {
+            name: Name::new("mRNA"),
+            start: StartMrna(mrna.clone()),
+            mrna,
+            codon_state: CodonState::default(),
+            history: EditHistory::default(),
+            cursor: EditorCursor::default(),
+        }
+    }
+}
+
+/// The bases the player can type, a puzzle level may leave some out.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct AllowedBases(pub Vec<NucleobaseRna>);
+
+impl Default for AllowedBases {
+    fn default() -> Self {
+        AllowedBases(vec![
+            NucleobaseRna::Uracil,
+            NucleobaseRna::Cytosine,
+            NucleobaseRna::Adenine,
+            NucleobaseRna::Guanine,
+        ])
+    }
+}
+
 #[derive(Resource)]
 pub struct LogTextRes {
     pub text: String,
@@ -44,7 +195,11 @@ pub struct LogTextRes {
 impl Default for LogTextRes {
     fn default() -> Self {
         LogTextRes {
-            text: "Press a triplet of (U, C, A, G) to build a codon of an mRNA sequence"
+            text: "Press a triplet of (U, C, A, G) to build a codon of an mRNA sequence\n\
+                   [ and ] pick which cell's mRNA to edit\n\
+                   Backspace deletes, M mutates the last base, Ctrl+Z and Ctrl+Y undo and redo\n\
+                   Comma, Period, Home and End move the cursor, Shift selects, Ctrl+C X V copy and paste\n\
+                   F3 hides a secret message in the mRNA, F4 reads it back"
                 .to_string(),
         }
     }
@@ -76,47 +231,109 @@ pub fn update_log(mut log_q: Query<&mut Text, With<Log>>, help_text: Res<LogText
     }
 }
 
+/// `[` and `]` move the selection through the mRNAs, one is selected whenever any exist.
+pub fn select_mrna(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mrna_q: Query<Entity, With<Mrna>>,
+    selected_q: Query<Entity, With<SelectedMrna>>,
+) {
+    let mut mrnas: Vec<Entity> = mrna_q.iter().collect();
+    if mrnas.is_empty() {
+        return;
+    }
+    mrnas.sort();
+    let selected = selected_q
+        .iter()
+        .next()
+        .and_then(|entity| mrnas.iter().position(|e| *e == entity));
+    let step = if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        1
+    } else if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        mrnas.len() - 1
+    } else {
+        0
+    };
+    let next = match selected {
+        Some(_) if step == 0 => return,
+        Some(index) => (index + step) % mrnas.len(),
+        None => 0,
+    };
+    for entity in &selected_q {
+        commands.entity(entity).remove::<SelectedMrna>();
+    }
+    commands.entity(mrnas[next]).insert(SelectedMrna);
+}
+
 pub fn build_codon(
-    commands: Commands,
+    mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut codon_state: ResMut<CodonState>,
-    mut mrna: ResMut<Mrna>,
-    mut log_res: ResMut<LogTextRes>,
+    mut mrna_q: Query<
+        (
+            Entity,
+            &mut Mrna,
+            &mut CodonState,
+            &mut EditHistory,
+            &EditorCursor,
+        ),
+        With<SelectedMrna>,
+    >,
+    active_table: Res<ActiveCodonTable>,
+    allowed_bases: Res<AllowedBases>,
+    history_settings: Res<HistorySettings>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::KeyU) {
-        codon_state.push(NucleobaseRna::Uracil)
-    }
-    if keyboard_input.just_pressed(KeyCode::KeyC) {
-        codon_state.push(NucleobaseRna::Cytosine)
-    }
-    if keyboard_input.just_pressed(KeyCode::KeyA) {
-        codon_state.push(NucleobaseRna::Adenine)
-    }
-    if keyboard_input.just_pressed(KeyCode::KeyG) {
-        codon_state.push(NucleobaseRna::Guanine)
-    }
-    let mut codon_option = codon_state.remove_head_codon();
-    while codon_option.is_some() {
-        let codon = codon_option.unwrap();
-        codon_option = codon_state.remove_head_codon();
-        let stop_codon = CODON_TABLE.translation(&codon) == '.' && mrna.has_start_codon;
-        mrna.push(codon);
-        if stop_codon {
-            spawn_acid(&commands, &mrna, &mut log_res);
+    let Ok((entity, mut mrna, mut codon_state, mut history, cursor)) = mrna_q.get_single_mut()
+    else {
+        return;
+    };
+    // with the cursor inside the sequence or Ctrl held, the editor takes the keys
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if ctrl || !cursor.is_appending() {
+        return;
+    }
+    let table = &active_table.table;
+    let mut step = Step {
+        edits: Vec::new(),
+        translated: false,
+    };
+    for (key, base) in [
+        (KeyCode::KeyU, NucleobaseRna::Uracil),
+        (KeyCode::KeyC, NucleobaseRna::Cytosine),
+        (KeyCode::KeyA, NucleobaseRna::Adenine),
+        (KeyCode::KeyG, NucleobaseRna::Guanine),
+    ] {
+        if keyboard_input.just_pressed(key) && allowed_bases.0.contains(&base) {
+            let edit = Edit::EnterBase(base);
+            edit.apply(&mut mrna, &mut codon_state);
+            step.edits.push(edit);
         }
     }
+    while let Some(codon) = codon_state.head_codon() {
+        let edit = Edit::CommitCodon(codon);
+        edit.apply(&mut mrna, &mut codon_state);
+        step.edits.push(edit);
+        if !table.is_stop(&codon) {
+            continue;
+        }
+        // earlier products were released by earlier stops, only one can end here
+        let end = mrna.codons().len();
+        if let Some(product) = mrna.products(table).pop().filter(|p| p.end == end) {
+            step.translated = true;
+            commands.trigger(ProductReleased {
+                entity,
+                mrna: mrna.clone(),
+                table: table.clone(),
+                product,
+            });
+        }
+    }
+    // only touch the history when something was typed, it is watched for changes
+    if !step.edits.is_empty() {
+        history.record(step, history_settings.limit);
+    }
 }
-/*
-enum NucleobaseDna {
-    Adenine,
-    Thymine,
-    Cytosine,
-    Guanine,
-}
-*/
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum NucleobaseRna {
+pub enum NucleobaseRna {
     Adenine,
     Uracil,
     Cytosine,
@@ -149,37 +366,74 @@ impl NucleobaseRna {
             NucleobaseRna::Guanine => 3,
         }
     }
+    /// The Watson-Crick partner of this base.
+    pub fn complement(&self) -> Self {
+        match self {
+            NucleobaseRna::Adenine => NucleobaseRna::Uracil,
+            NucleobaseRna::Uracil => NucleobaseRna::Adenine,
+            NucleobaseRna::Cytosine => NucleobaseRna::Guanine,
+            NucleobaseRna::Guanine => NucleobaseRna::Cytosine,
+        }
+    }
+    pub fn is_gc(&self) -> bool {
+        matches!(self, NucleobaseRna::Cytosine | NucleobaseRna::Guanine)
+    }
 }
 
-#[derive(Resource, Debug, Clone, PartialEq)]
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct Mrna {
     codons: Vec<Codon>,
-    has_start_codon: bool,
+    /// Ribosomes only load onto capped messages, see [`lifecycle`].
+    capped: bool,
+    poly_a_length: u32,
 }
 
 impl Default for Mrna {
     fn default() -> Self {
         Mrna {
             codons: Vec::new(),
-            has_start_codon: false,
+            capped: true,
+            poly_a_length: lifecycle::POLY_A_LENGTH,
         }
     }
 }
 
 impl Mrna {
+    /// Build an mRNA from a literal, panics on anything [`str::parse`] would reject.
     pub fn new(s: &str) -> Self {
-        let mut codons = Vec::new();
-        for i in (0..s.len()).step_by(3) {
-            let b1 = s.chars().nth(i).unwrap();
-            let b2 = s.chars().nth(i + 1).unwrap();
-            let b3 = s.chars().nth(i + 2).unwrap();
-            codons.push(Codon::new(b1, b2, b3));
+        s.parse()
+            .unwrap_or_else(|e| panic!("invalid mRNA {s:?}: {e}"))
+    }
+    /// Build an mRNA from loose bases, a trailing partial codon is dropped.
+    pub fn from_bases(bases: &[NucleobaseRna]) -> Self {
+        let mut mrna = Mrna::default();
+        for chunk in bases.chunks_exact(3) {
+            mrna.push(Codon::from_bases((chunk[0], chunk[1], chunk[2])));
         }
-        Mrna {
-            codons,
-            has_start_codon: s.contains("AUG"),
+        mrna
+    }
+    pub fn codons(&self) -> &[Codon] {
+        &self.codons
+    }
+    pub fn bases(&self) -> Vec<NucleobaseRna> {
+        let mut bases = Vec::with_capacity(self.codons.len() * 3);
+        for codon in &self.codons {
+            bases.extend([codon.bases.0, codon.bases.1, codon.bases.2]);
         }
+        bases
+    }
+    pub fn to_string_bases(&self) -> String {
+        let mut s = String::new();
+        for codon in &self.codons {
+            s.push(codon.bases.0.to_char());
+            s.push(codon.bases.1.to_char());
+            s.push(codon.bases.2.to_char());
+        }
+        s
     }
+    /// Every codon with the built-in Toki Pona table, start or not,
+    /// never the F2 code or a reloaded `toki_pona.codons.ron`.
+    /// See [`Mrna::products`] for what a ribosome makes with a given code.
     pub fn to_string_toki(&self) -> String {
         let mut s = String::new();
         for codon in &self.codons {
@@ -187,16 +441,11 @@ impl Mrna {
         }
         s
     }
+    /// The start to stop products a ribosome would release reading along the whole message.
+    pub fn products(&self, table: &NucleobaseRnaToAminoChar) -> Vec<Product> {
+        table.products(&self.codons)
+    }
     pub fn push(&mut self, codon: Codon) {
-        if codon.bases
-            == (
-                NucleobaseRna::Adenine,
-                NucleobaseRna::Uracil,
-                NucleobaseRna::Guanine,
-            )
-        {
-            self.has_start_codon = true;
-        }
         self.codons.push(codon);
     }
 }
@@ -210,13 +459,11 @@ impl Codon {
     pub fn from_bases(bases: (NucleobaseRna, NucleobaseRna, NucleobaseRna)) -> Self {
         Codon { bases }
     }
+    /// Build a codon from literal bases, panics on anything but U, C, A or G.
     pub fn new(b1: char, b2: char, b3: char) -> Self {
+        let base = |c: char| NucleobaseRna::try_from(c).unwrap_or_else(|e| panic!("{e}"));
         Codon {
-            bases: (
-                NucleobaseRna::from_char(b1).unwrap(),
-                NucleobaseRna::from_char(b2).unwrap(),
-                NucleobaseRna::from_char(b3).unwrap(),
-            ),
+            bases: (base(b1), base(b2), base(b3)),
         }
     }
     pub fn from_index(i: usize) -> Self {
@@ -227,6 +474,10 @@ impl Codon {
             bases: (b1, b2, b3),
         }
     }
+    /// Position in chart order, the inverse of [`Codon::from_index`].
+    pub fn to_index(&self) -> usize {
+        self.i0() * 16 + self.i1() * 4 + self.i2()
+    }
     pub fn i0(&self) -> usize {
         self.bases.0.to_index()
     }
@@ -236,6 +487,34 @@ impl Codon {
     pub fn i2(&self) -> usize {
         self.bases.2.to_index()
     }
+    pub fn gc_count(&self) -> usize {
+        [self.bases.0, self.bases.1, self.bases.2]
+            .iter()
+            .filter(|b| b.is_gc())
+            .count()
+    }
+    /// The tRNA anticodon that pairs with this codon, read 5' to 3'.
+    pub fn anticodon(&self) -> Codon {
+        Codon {
+            bases: (
+                self.bases.2.complement(),
+                self.bases.1.complement(),
+                self.bases.0.complement(),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Codon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.bases.0.to_char(),
+            self.bases.1.to_char(),
+            self.bases.2.to_char()
+        )
+    }
 }
 
 fn base_from_last_index(i: usize) -> NucleobaseRna {
@@ -256,4 +535,26 @@ mod test {
         let mrna = Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA");
         assert_eq!(mrna.to_string_toki(), "^Toki Pona.");
     }
+    #[test]
+    fn test_new_products() {
+        let before = Mrna::new("AUGACUCAGCGAAUC");
+        let after = Mrna::new("AUGACUCAGCGAAUCUAA");
+        let new = new_products(&before, &after, &CODON_TABLE);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].text, "^Toki.");
+        // shifting a finished product along doesn't finish it again
+        let shifted = Mrna::new("GUUAUGACUCAGCGAAUCUAA");
+        assert!(new_products(&after, &shifted, &CODON_TABLE).is_empty());
+        // but a second copy is new
+        let twice = Mrna::new("AUGACUCAGCGAAUCUAAAUGACUCAGCGAAUCUAA");
+        assert_eq!(new_products(&after, &twice, &CODON_TABLE).len(), 1);
+    }
+    #[test]
+    fn test_anticodon() {
+        assert_eq!(
+            Codon::new('A', 'U', 'G').anticodon(),
+            Codon::new('C', 'A', 'U')
+        );
+        assert_eq!(Codon::new('G', 'C', 'U').anticodon().to_string(), "AGC");
+    }
 }
@@ -0,0 +1,393 @@
+//! Pairwise alignment with affine gaps (Gotoh's form of the dynamic programming),
+//! global ([Needleman-Wunsch](https://en.wikipedia.org/wiki/Needleman%E2%80%93Wunsch_algorithm))
+//! or local ([Smith-Waterman](https://en.wikipedia.org/wiki/Smith%E2%80%93Waterman_algorithm)).
+//! Works on bases or on translated text, to grade how close the player got to a target.
+
+use super::{translation::NucleobaseRnaToAminoChar, Mrna, NucleobaseRna};
+
+/// Well below any real score, but far enough from `i32::MIN` that adding penalties can't wrap.
+const NEG_INF: i32 = i32::MIN / 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentMode {
+    /// Both sequences end to end.
+    Global,
+    /// The best scoring pair of stretches, anything else is left out.
+    Local,
+}
+
+/// A gap of length `k` scores `gap_open + (k - 1) * gap_extend`, so penalties are negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scoring {
+    pub matched: i32,
+    pub mismatched: i32,
+    pub gap_open: i32,
+    pub gap_extend: i32,
+}
+
+impl Scoring {
+    /// Roughly the BLASTN defaults.
+    pub const NUCLEOTIDE: Scoring = Scoring {
+        matched: 2,
+        mismatched: -3,
+        gap_open: -5,
+        gap_extend: -2,
+    };
+    /// For translated text, where a wrong character is as bad as a missing one.
+    pub const TRANSLATED: Scoring = Scoring {
+        matched: 4,
+        mismatched: -2,
+        gap_open: -6,
+        gap_extend: -1,
+    };
+    /// Same cost for opening and extending a gap.
+    pub const fn linear(matched: i32, mismatched: i32, gap: i32) -> Self {
+        Scoring {
+            matched,
+            mismatched,
+            gap_open: gap,
+            gap_extend: gap,
+        }
+    }
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Scoring::NUCLEOTIDE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    /// `a[i]` against `b[j]`.
+    Pair,
+    /// `a[i]` against a gap.
+    GapInB,
+    /// A gap against `b[j]`.
+    GapInA,
+}
+
+/// One column of an alignment, `None` is a gap.
+pub type Column<T> = (Option<T>, Option<T>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment<T> {
+    pub columns: Vec<Column<T>>,
+    pub score: i32,
+    /// Where the aligned stretch of `a` starts, 0 for a global alignment.
+    pub a_start: usize,
+    pub b_start: usize,
+}
+
+impl<T: PartialEq> Alignment<T> {
+    pub fn matches(&self) -> usize {
+        self.columns
+            .iter()
+            .filter(|(a, b)| a.is_some() && a == b)
+            .count()
+    }
+    /// Fraction of columns that match, 0 for an empty alignment.
+    pub fn identity(&self) -> f32 {
+        if self.columns.is_empty() {
+            return 0.0;
+        }
+        self.matches() as f32 / self.columns.len() as f32
+    }
+    /// Three lines for a diff view: `a`, then `|` for a match, `.` for a mismatch and
+    /// a space for a gap, then `b`. Gaps print as `-`.
+    pub fn render(&self, to_char: impl Fn(&T) -> char) -> [String; 3] {
+        let mut lines = [String::new(), String::new(), String::new()];
+        for (a, b) in &self.columns {
+            lines[0].push(a.as_ref().map_or('-', &to_char));
+            lines[1].push(match (a, b) {
+                (Some(a), Some(b)) if a == b => '|',
+                (Some(_), Some(_)) => '.',
+                _ => ' ',
+            });
+            lines[2].push(b.as_ref().map_or('-', &to_char));
+        }
+        lines
+    }
+}
+
+/// Align with `scoring`'s match and mismatch scores.
+pub fn align<T: Copy + PartialEq>(
+    a: &[T],
+    b: &[T],
+    mode: AlignmentMode,
+    scoring: &Scoring,
+) -> Alignment<T> {
+    align_by(a, b, mode, scoring.gap_open, scoring.gap_extend, |x, y| {
+        if x == y {
+            scoring.matched
+        } else {
+            scoring.mismatched
+        }
+    })
+}
+
+/// Align with any substitution score, like a BLOSUM matrix for amino acids.
+pub fn align_by<T: Copy>(
+    a: &[T],
+    b: &[T],
+    mode: AlignmentMode,
+    gap_open: i32,
+    gap_extend: i32,
+    substitution: impl Fn(&T, &T) -> i32,
+) -> Alignment<T> {
+    let (n, m) = (a.len(), b.len());
+    // best score ending in each state, and the state before it
+    let (mut pair, mut gap_b, mut gap_a) = (
+        grid(n, m, NEG_INF),
+        grid(n, m, NEG_INF),
+        grid(n, m, NEG_INF),
+    );
+    let (mut pair_from, mut gap_b_from, mut gap_a_from) = (
+        grid(n, m, State::Start),
+        grid(n, m, State::Start),
+        grid(n, m, State::Start),
+    );
+    pair[0][0] = 0;
+    if mode == AlignmentMode::Global {
+        for i in 1..=n {
+            gap_b[i][0] = gap_open + (i as i32 - 1) * gap_extend;
+            gap_b_from[i][0] = if i == 1 { State::Pair } else { State::GapInB };
+        }
+        for j in 1..=m {
+            gap_a[0][j] = gap_open + (j as i32 - 1) * gap_extend;
+            gap_a_from[0][j] = if j == 1 { State::Pair } else { State::GapInA };
+        }
+    }
+    let best_of = |options: [(i32, State); 3]| {
+        options
+            .into_iter()
+            .fold((NEG_INF, State::Start), |best, option| {
+                if option.0 > best.0 {
+                    option
+                } else {
+                    best
+                }
+            })
+    };
+    let mut best_local = (0, 0, 0);
+    for i in 1..=n {
+        for j in 1..=m {
+            let (before, from) = best_of([
+                (pair[i - 1][j - 1], State::Pair),
+                (gap_b[i - 1][j - 1], State::GapInB),
+                (gap_a[i - 1][j - 1], State::GapInA),
+            ]);
+            let (before, from) = if mode == AlignmentMode::Local && before <= 0 {
+                (0, State::Start)
+            } else {
+                (before, from)
+            };
+            pair[i][j] = before + substitution(&a[i - 1], &b[j - 1]);
+            pair_from[i][j] = from;
+            (gap_b[i][j], gap_b_from[i][j]) = best_of([
+                (pair[i - 1][j] + gap_open, State::Pair),
+                (gap_b[i - 1][j] + gap_extend, State::GapInB),
+                (gap_a[i - 1][j] + gap_open, State::GapInA),
+            ]);
+            (gap_a[i][j], gap_a_from[i][j]) = best_of([
+                (pair[i][j - 1] + gap_open, State::Pair),
+                (gap_a[i][j - 1] + gap_extend, State::GapInA),
+                (gap_b[i][j - 1] + gap_open, State::GapInB),
+            ]);
+            if pair[i][j] > best_local.0 {
+                best_local = (pair[i][j], i, j);
+            }
+        }
+    }
+    let (score, mut i, mut j, mut state) = match mode {
+        AlignmentMode::Global => {
+            let (score, state) = best_of([
+                (pair[n][m], State::Pair),
+                (gap_b[n][m], State::GapInB),
+                (gap_a[n][m], State::GapInA),
+            ]);
+            // two empty sequences, or one empty so only its gap state is set
+            let state = if n == 0 || m == 0 {
+                match (n, m) {
+                    (0, 0) => State::Start,
+                    (_, 0) => State::GapInB,
+                    _ => State::GapInA,
+                }
+            } else {
+                state
+            };
+            let score = if n == 0 && m == 0 { 0 } else { score };
+            (score, n, m, state)
+        }
+        AlignmentMode::Local => {
+            let (score, i, j) = best_local;
+            let state = if score > 0 { State::Pair } else { State::Start };
+            (score, i, j, state)
+        }
+    };
+    let mut columns = Vec::new();
+    while state != State::Start && (i > 0 || j > 0) {
+        let from = match state {
+            State::Pair => {
+                columns.push((Some(a[i - 1]), Some(b[j - 1])));
+                let from = pair_from[i][j];
+                (i, j) = (i - 1, j - 1);
+                from
+            }
+            State::GapInB => {
+                columns.push((Some(a[i - 1]), None));
+                let from = gap_b_from[i][j];
+                i -= 1;
+                from
+            }
+            State::GapInA => {
+                columns.push((None, Some(b[j - 1])));
+                let from = gap_a_from[i][j];
+                j -= 1;
+                from
+            }
+            State::Start => unreachable!(),
+        };
+        state = from;
+    }
+    columns.reverse();
+    Alignment {
+        columns,
+        score,
+        a_start: i,
+        b_start: j,
+    }
+}
+
+fn grid<V: Copy>(n: usize, m: usize, value: V) -> Vec<Vec<V>> {
+    vec![vec![value; m + 1]; n + 1]
+}
+
+impl Mrna {
+    /// Align the bases of this mRNA (as `a`) against `target` (as `b`).
+    pub fn align_bases(
+        &self,
+        target: &Mrna,
+        mode: AlignmentMode,
+        scoring: &Scoring,
+    ) -> Alignment<NucleobaseRna> {
+        align(&self.bases(), &target.bases(), mode, scoring)
+    }
+
+    /// Align what the two messages say in `table`, codon by codon.
+    pub fn align_translation(
+        &self,
+        target: &Mrna,
+        table: &NucleobaseRnaToAminoChar,
+        mode: AlignmentMode,
+        scoring: &Scoring,
+    ) -> Alignment<char> {
+        let translate = |mrna: &Mrna| -> Vec<char> {
+            mrna.codons().iter().map(|c| table.translation(c)).collect()
+        };
+        align(&translate(self), &translate(target), mode, scoring)
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::CODON_TABLE;
+
+    #[allow(dead_code)]
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+    #[allow(dead_code)]
+    fn render(alignment: &Alignment<char>) -> [String; 3] {
+        alignment.render(|c| *c)
+    }
+
+    #[test]
+    fn test_global() {
+        let alignment = align(
+            &chars("GATTACA"),
+            &chars("GCATGCU"),
+            AlignmentMode::Global,
+            &Scoring::linear(1, -1, -1),
+        );
+        assert_eq!(alignment.score, 0);
+        let [a, _, b] = render(&alignment);
+        assert_eq!(a.replace('-', ""), "GATTACA");
+        assert_eq!(b.replace('-', ""), "GCATGCU");
+        assert_eq!(a.len(), b.len());
+    }
+
+    #[test]
+    fn test_local() {
+        let alignment = align(
+            &chars("TGTTACGG"),
+            &chars("GGTTGACTA"),
+            AlignmentMode::Local,
+            &Scoring::linear(3, -3, -2),
+        );
+        assert_eq!(alignment.score, 13);
+        assert_eq!(render(&alignment), ["GTT-AC", "||| ||", "GTTGAC"]);
+        assert_eq!((alignment.a_start, alignment.b_start), (1, 1));
+    }
+
+    #[test]
+    fn test_affine_gap_stays_together() {
+        let scoring = Scoring {
+            matched: 2,
+            mismatched: -3,
+            gap_open: -5,
+            gap_extend: -1,
+        };
+        let alignment = align(
+            &chars("AUGCCCCCCUAA"),
+            &chars("AUGUAA"),
+            AlignmentMode::Global,
+            &scoring,
+        );
+        assert_eq!(alignment.score, 6 * 2 - 5 - 5);
+        assert_eq!(render(&alignment)[2], "AUG------UAA");
+        assert_eq!(alignment.matches(), 6);
+    }
+
+    #[test]
+    fn test_empty() {
+        let alignment = align(
+            &chars("AUG"),
+            &[],
+            AlignmentMode::Global,
+            &Scoring::default(),
+        );
+        assert_eq!(render(&alignment), ["AUG", "   ", "---"]);
+        assert_eq!(alignment.score, -5 - 2 * 2);
+        let alignment = align(
+            &chars("AUG"),
+            &chars("CCC"),
+            AlignmentMode::Local,
+            &Scoring::default(),
+        );
+        assert!(alignment.columns.is_empty());
+        assert_eq!(alignment.identity(), 0.0);
+    }
+
+    #[test]
+    fn test_mrna() {
+        let target = Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA");
+        let attempt = Mrna::new("AUGACUCAGAUAGUUCCUCAGAACGCGUGA");
+        let bases = attempt.align_bases(&target, AlignmentMode::Global, &Scoring::NUCLEOTIDE);
+        assert_eq!(bases.matches(), 30);
+        let text = attempt.align_translation(
+            &target,
+            &CODON_TABLE,
+            AlignmentMode::Global,
+            &Scoring::TRANSLATED,
+        );
+        assert_eq!(
+            text.render(|c| *c),
+            ["^To-i Pona.", "||| |||||||", "^Toki Pona."]
+        );
+        assert!((text.identity() - 10.0 / 11.0).abs() < 0.001);
+    }
+}
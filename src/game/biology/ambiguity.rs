@@ -0,0 +1,274 @@
+//! [IUPAC ambiguity codes](https://www.bioinformatics.org/sms/iupac.html) for bases that are
+//! only partly known, like `N` for any base or `R` for a purine, and translation of codons
+//! made of them into every character they could stand for.
+
+use super::{parse::SequenceError, translation::NucleobaseRnaToAminoChar, Codon, NucleobaseRna};
+
+/// Chart order, so expansions come out in the same order as the codon chart.
+const BASES: [NucleobaseRna; 4] = [
+    NucleobaseRna::Uracil,
+    NucleobaseRna::Cytosine,
+    NucleobaseRna::Adenine,
+    NucleobaseRna::Guanine,
+];
+
+/// Codes and the bases they stand for,
+/// bit `i` set for the base with [`NucleobaseRna::to_index`] `i`.
+const CODES: [(char, u8); 15] = [
+    ('U', 0b0001),
+    ('C', 0b0010),
+    ('A', 0b0100),
+    ('G', 0b1000),
+    ('R', 0b1100),
+    ('Y', 0b0011),
+    ('S', 0b1010),
+    ('W', 0b0101),
+    ('K', 0b1001),
+    ('M', 0b0110),
+    ('B', 0b1011),
+    ('D', 0b1101),
+    ('H', 0b0111),
+    ('V', 0b1110),
+    ('N', 0b1111),
+];
+
+/// A set of one to four bases written as one IUPAC letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AmbiguousBase(u8);
+
+impl AmbiguousBase {
+    pub const ANY: AmbiguousBase = AmbiguousBase(0b1111);
+
+    /// Upper or lower case, DNA `T` is read as `U`.
+    pub fn from_char(c: char) -> Option<Self> {
+        let c = match c.to_ascii_uppercase() {
+            'T' => 'U',
+            upper => upper,
+        };
+        CODES
+            .iter()
+            .find(|(code, _)| *code == c)
+            .map(|(_, mask)| AmbiguousBase(*mask))
+    }
+    pub fn to_char(&self) -> char {
+        CODES
+            .iter()
+            .find(|(_, mask)| *mask == self.0)
+            .map_or('N', |(code, _)| *code)
+    }
+    pub fn contains(&self, base: NucleobaseRna) -> bool {
+        self.0 & (1 << base.to_index()) != 0
+    }
+    /// The bases this could be, in chart order.
+    pub fn bases(&self) -> Vec<NucleobaseRna> {
+        BASES.into_iter().filter(|b| self.contains(*b)).collect()
+    }
+    /// The base, if only one is possible.
+    pub fn definite(&self) -> Option<NucleobaseRna> {
+        match self.bases().as_slice() {
+            [base] => Some(*base),
+            _ => None,
+        }
+    }
+    /// The complement of every base in the set, `R` pairs with `Y`.
+    pub fn complement(&self) -> Self {
+        let mut mask = 0;
+        for base in self.bases() {
+            mask |= 1 << base.complement().to_index();
+        }
+        AmbiguousBase(mask)
+    }
+}
+
+impl From<NucleobaseRna> for AmbiguousBase {
+    fn from(base: NucleobaseRna) -> Self {
+        AmbiguousBase(1 << base.to_index())
+    }
+}
+
+impl std::fmt::Display for AmbiguousBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+/// Parse IUPAC codes, `-` and whitespace are not allowed.
+pub fn parse_ambiguous_bases(s: &str) -> Result<Vec<AmbiguousBase>, SequenceError> {
+    s.chars()
+        .enumerate()
+        .map(|(position, c)| {
+            AmbiguousBase::from_char(c).ok_or(SequenceError::InvalidBase { position, c })
+        })
+        .collect()
+}
+
+/// The bases as plain bases, `None` if any of them is ambiguous.
+pub fn definite_bases(bases: &[AmbiguousBase]) -> Option<Vec<NucleobaseRna>> {
+    bases.iter().map(AmbiguousBase::definite).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AmbiguousCodon(pub [AmbiguousBase; 3]);
+
+impl AmbiguousCodon {
+    /// Every codon this could be, in chart order.
+    pub fn expansions(&self) -> Vec<Codon> {
+        let [b0, b1, b2] = self.0;
+        let mut codons = Vec::new();
+        for x in b0.bases() {
+            for y in b1.bases() {
+                for z in b2.bases() {
+                    codons.push(Codon::from_bases((x, y, z)));
+                }
+            }
+        }
+        codons
+    }
+}
+
+impl From<Codon> for AmbiguousCodon {
+    fn from(codon: Codon) -> Self {
+        let (b0, b1, b2) = codon.bases;
+        AmbiguousCodon([b0.into(), b1.into(), b2.into()])
+    }
+}
+
+impl std::fmt::Display for AmbiguousCodon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [b0, b1, b2] = self.0;
+        write!(f, "{b0}{b1}{b2}")
+    }
+}
+
+/// What an ambiguous codon translates to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmbiguousTranslation {
+    /// Every expansion gives this character.
+    Single(char),
+    /// The distinct characters, in chart order of the first codon giving each.
+    Either(Vec<char>),
+}
+
+impl std::fmt::Display for AmbiguousTranslation {
+    /// A single character as is, alternatives in brackets like `[FL]`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmbiguousTranslation::Single(c) => write!(f, "{c}"),
+            AmbiguousTranslation::Either(cs) => {
+                write!(f, "[{}]", cs.iter().collect::<String>())
+            }
+        }
+    }
+}
+
+impl NucleobaseRnaToAminoChar {
+    pub fn translate_ambiguous(&self, codon: &AmbiguousCodon) -> AmbiguousTranslation {
+        let mut chars: Vec<char> = Vec::new();
+        for codon in codon.expansions() {
+            let c = self.translation(&codon);
+            if !chars.contains(&c) {
+                chars.push(c);
+            }
+        }
+        match chars.as_slice() {
+            [c] => AmbiguousTranslation::Single(*c),
+            _ => AmbiguousTranslation::Either(chars),
+        }
+    }
+
+    /// Translate codon by codon, a trailing partial codon is dropped.
+    pub fn translate_ambiguous_bases(&self, bases: &[AmbiguousBase]) -> Vec<AmbiguousTranslation> {
+        bases
+            .chunks_exact(3)
+            .map(|c| self.translate_ambiguous(&AmbiguousCodon([c[0], c[1], c[2]])))
+            .collect()
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::{
+        translation::{NucleobaseRnaToAminoChar, TranslationMode},
+        CODON_TABLE,
+    };
+
+    #[allow(dead_code)]
+    fn codon(s: &str) -> AmbiguousCodon {
+        let bases = parse_ambiguous_bases(s).unwrap();
+        AmbiguousCodon([bases[0], bases[1], bases[2]])
+    }
+
+    #[test]
+    fn test_codes() {
+        for (code, _) in CODES {
+            assert_eq!(AmbiguousBase::from_char(code).unwrap().to_char(), code);
+        }
+        assert_eq!(AmbiguousBase::from_char('t').unwrap().to_char(), 'U');
+        assert_eq!(
+            AmbiguousBase::from_char('R').unwrap().bases(),
+            vec![NucleobaseRna::Adenine, NucleobaseRna::Guanine]
+        );
+        assert_eq!(AmbiguousBase::ANY.bases().len(), 4);
+        assert_eq!(
+            AmbiguousBase::from_char('G').unwrap().definite(),
+            Some(NucleobaseRna::Guanine)
+        );
+        assert_eq!(AmbiguousBase::from_char('Y').unwrap().definite(), None);
+        assert_eq!(
+            AmbiguousBase::from_char('R')
+                .unwrap()
+                .complement()
+                .to_char(),
+            'Y'
+        );
+        assert_eq!(
+            AmbiguousBase::from_char('B')
+                .unwrap()
+                .complement()
+                .to_char(),
+            'V'
+        );
+        assert_eq!(
+            parse_ambiguous_bases("ANX"),
+            Err(SequenceError::InvalidBase {
+                position: 2,
+                c: 'X'
+            })
+        );
+        assert_eq!(
+            definite_bases(&parse_ambiguous_bases("AUG").unwrap()),
+            Some(vec![
+                NucleobaseRna::Adenine,
+                NucleobaseRna::Uracil,
+                NucleobaseRna::Guanine
+            ])
+        );
+        assert_eq!(definite_bases(&parse_ambiguous_bases("AUN").unwrap()), None);
+    }
+
+    #[test]
+    fn test_translate() {
+        let real = NucleobaseRnaToAminoChar::new(TranslationMode::Real);
+        assert_eq!(codon("AUG").expansions().len(), 1);
+        assert_eq!(codon("NNN").expansions().len(), 64);
+        // the third position of a four-fold box doesn't matter
+        assert_eq!(
+            real.translate_ambiguous(&codon("GCN")),
+            AmbiguousTranslation::Single('A')
+        );
+        // UUY is phenylalanine, UUR leucine
+        assert_eq!(
+            real.translate_ambiguous(&codon("UUN")),
+            AmbiguousTranslation::Either(vec!['F', 'L'])
+        );
+        assert_eq!(real.translate_ambiguous(&codon("UUN")).to_string(), "[FL]");
+        let translated: String = CODON_TABLE
+            .translate_ambiguous_bases(&parse_ambiguous_bases("AUGACUCAGCGAAUMUAR").unwrap())
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(translated, "^Toki.");
+    }
+}
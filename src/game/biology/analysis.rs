@@ -0,0 +1,277 @@
+//! Composition statistics of the sequence being typed, and a panel that shows them.
+
+use bevy::prelude::*;
+
+use super::{
+    codon_table::ActiveCodonTable, history::EditHistory, translation::NucleobaseRnaToAminoChar,
+    Codon, CodonState, Mrna, NucleobaseRna, SelectedMrna,
+};
+use crate::{game::puzzle::Puzzle, screen::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Playing), spawn_stats_panel);
+    app.add_systems(Update, update_stats_panel.run_if(in_state(Screen::Playing)));
+}
+
+/// Counts over a run of bases, codon statistics only use the whole codons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceStats {
+    /// Indexed by [`NucleobaseRna::to_index`].
+    pub base_counts: [usize; 4],
+    /// G and C counts at the first, second and third codon position.
+    pub gc_by_position: [usize; 3],
+    /// Indexed by [`Codon::to_index`].
+    pub codon_usage: [usize; 64],
+}
+
+impl SequenceStats {
+    pub fn from_bases(bases: &[NucleobaseRna]) -> Self {
+        let mut stats = SequenceStats {
+            base_counts: [0; 4],
+            gc_by_position: [0; 3],
+            codon_usage: [0; 64],
+        };
+        for base in bases {
+            stats.base_counts[base.to_index()] += 1;
+        }
+        for chunk in bases.chunks_exact(3) {
+            let codon = Codon::from_bases((chunk[0], chunk[1], chunk[2]));
+            stats.codon_usage[codon.to_index()] += 1;
+            for (position, base) in chunk.iter().enumerate() {
+                if base.is_gc() {
+                    stats.gc_by_position[position] += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    /// The finished codons of `mrna` followed by the bases still being typed.
+    pub fn from_sequence(mrna: &Mrna, codon_state: &CodonState) -> Self {
+        let mut bases = mrna.bases();
+        bases.extend(&codon_state.bases);
+        SequenceStats::from_bases(&bases)
+    }
+
+    pub fn base_count(&self, base: NucleobaseRna) -> usize {
+        self.base_counts[base.to_index()]
+    }
+    pub fn len(&self) -> usize {
+        self.base_counts.iter().sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn codon_count(&self) -> usize {
+        self.codon_usage.iter().sum()
+    }
+
+    /// Fraction of bases that are G or C, 0 for no bases.
+    pub fn gc_content(&self) -> f32 {
+        let gc = self.base_count(NucleobaseRna::Guanine) + self.base_count(NucleobaseRna::Cytosine);
+        fraction(gc, self.len())
+    }
+
+    /// GC content at codon position 0, 1 or 2 (GC1, GC2, GC3).
+    pub fn gc_content_at(&self, position: usize) -> f32 {
+        fraction(self.gc_by_position[position], self.codon_count())
+    }
+
+    /// Relative synonymous codon usage: how often each codon is used compared to
+    /// using every codon for the same character equally, 1.0 is no bias.
+    /// `None` for codons whose character never appears.
+    pub fn rscu(&self, table: &NucleobaseRnaToAminoChar) -> [Option<f32>; 64] {
+        let mut rscu = [None; 64];
+        for (i, value) in rscu.iter_mut().enumerate() {
+            let synonyms = table.codons_for(table.translation(&Codon::from_index(i)));
+            let used: usize = synonyms
+                .iter()
+                .map(|c| self.codon_usage[c.to_index()])
+                .sum();
+            if used > 0 {
+                *value = Some(self.codon_usage[i] as f32 * synonyms.len() as f32 / used as f32);
+            }
+        }
+        rscu
+    }
+}
+
+fn fraction(count: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f32 / total as f32
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatsPanel;
+
+fn spawn_stats_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("StatsPanel"),
+        StatsPanel,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.0),
+            right: Val::Px(20.0),
+            ..default()
+        }),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+/// Shows the selected mRNA, refreshed when it changes or another one is selected.
+fn update_stats_panel(
+    selected_q: Query<(
+        Ref<Mrna>,
+        Ref<CodonState>,
+        Ref<EditHistory>,
+        Ref<SelectedMrna>,
+    )>,
+    active_table: Res<ActiveCodonTable>,
+    puzzle: Res<Puzzle>,
+    mut panel_q: Query<&mut Text, With<StatsPanel>>,
+    added_q: Query<(), Added<StatsPanel>>,
+) {
+    let Ok((mrna, codon_state, history, selected)) = selected_q.get_single() else {
+        return;
+    };
+    if !mrna.is_changed()
+        && !codon_state.is_changed()
+        && !history.is_changed()
+        && !selected.is_added()
+        && !active_table.is_changed()
+        && !puzzle.is_changed()
+        && added_q.is_empty()
+    {
+        return;
+    }
+    let stats = SequenceStats::from_sequence(&mrna, &codon_state);
+    let translated = match history.since_translation() {
+        Some(steps) => format!(", translated {steps} steps back"),
+        None => String::new(),
+    };
+    let text = format!(
+        "{}, poly-A tail {}\n{} undo, {} redo{}\n{}",
+        if mrna.is_capped() {
+            "capped"
+        } else {
+            "uncapped"
+        },
+        mrna.poly_a_length(),
+        history.undo_len(),
+        history.redo_len(),
+        translated,
+        describe_stats(&stats, &active_table.table, puzzle.level.is_none())
+    );
+    for mut panel in &mut panel_q {
+        panel.sections[0].value = text.clone();
+    }
+}
+
+fn describe_stats(
+    stats: &SequenceStats,
+    table: &NucleobaseRnaToAminoChar,
+    free_play: bool,
+) -> String {
+    let mut text = table.name().to_string();
+    if free_play {
+        text.push_str(" (F2 for the next code)");
+    }
+    text.push_str(&format!(
+        "\n{} bases, {} codons",
+        stats.len(),
+        stats.codon_count()
+    ));
+    for base in [
+        NucleobaseRna::Uracil,
+        NucleobaseRna::Cytosine,
+        NucleobaseRna::Adenine,
+        NucleobaseRna::Guanine,
+    ] {
+        text.push_str(&format!("  {} {}", base.to_char(), stats.base_count(base)));
+    }
+    text.push_str(&format!(
+        "\nGC {:.0}%  GC1 {:.0}%  GC2 {:.0}%  GC3 {:.0}%",
+        stats.gc_content() * 100.0,
+        stats.gc_content_at(0) * 100.0,
+        stats.gc_content_at(1) * 100.0,
+        stats.gc_content_at(2) * 100.0,
+    ));
+    text.push_str("\ncodon char count RSCU");
+    let rscu = stats.rscu(table);
+    // one chart row per line, like the codon chart: first and third base fixed per line
+    for row in 0..16 {
+        text.push('\n');
+        for column in 0..4 {
+            let i = (row / 4) * 16 + column * 4 + row % 4;
+            let codon = Codon::from_index(i);
+            let rscu = rscu[i].map_or("-".to_string(), |r| format!("{r:.2}"));
+            text.push_str(&format!(
+                "{} {} {:>2} {:>4}   ",
+                codon,
+                table.translation(&codon),
+                stats.codon_usage[i],
+                rscu
+            ));
+        }
+    }
+    text
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::CODON_TABLE;
+    #[test]
+    fn test_composition() {
+        let stats = SequenceStats::from_bases(&Mrna::new("AUGGCC").bases());
+        assert_eq!(stats.base_count(NucleobaseRna::Guanine), 2);
+        assert_eq!(stats.base_count(NucleobaseRna::Cytosine), 2);
+        assert_eq!(stats.len(), 6);
+        assert!((stats.gc_content() - 4.0 / 6.0).abs() < 0.001);
+        // AUG and GCC: G in the first position once, C once in the second, G and C third
+        assert_eq!(stats.gc_by_position, [1, 1, 2]);
+        assert_eq!(stats.gc_content_at(2), 1.0);
+        assert_eq!(SequenceStats::from_bases(&[]).gc_content(), 0.0);
+    }
+    #[test]
+    fn test_partial_codon() {
+        let mut codon_state = CodonState::default();
+        codon_state.push(NucleobaseRna::Guanine);
+        let stats = SequenceStats::from_sequence(&Mrna::new("AUG"), &codon_state);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.codon_count(), 1);
+        assert_eq!(stats.codon_usage[Codon::new('A', 'U', 'G').to_index()], 1);
+    }
+    #[test]
+    fn test_rscu() {
+        // two of the codons for k, one used twice, one once
+        let stats = SequenceStats::from_bases(&Mrna::new("CGACGACGG").bases());
+        let rscu = stats.rscu(&CODON_TABLE);
+        let k = CODON_TABLE.codons_for('k').len() as f32;
+        assert_eq!(
+            rscu[Codon::new('C', 'G', 'A').to_index()],
+            Some(2.0 * k / 3.0)
+        );
+        assert_eq!(rscu[Codon::new('C', 'G', 'G').to_index()], Some(k / 3.0));
+        for codon in CODON_TABLE.codons_for('k') {
+            if !matches!(codon.to_string().as_str(), "CGA" | "CGG") {
+                assert_eq!(rscu[codon.to_index()], Some(0.0));
+            }
+        }
+        assert_eq!(rscu[Codon::new('A', 'U', 'G').to_index()], None);
+        for i in 0..64 {
+            assert_eq!(Codon::from_index(i).to_index(), i);
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Codon tables written as RON under `assets/codes/`, so new cipher languages need no rebuild.
+//! `.codons.ron` files load as [`CodonTable`] assets, the one in [`ActiveCodonTable`]
+//! is what the game translates with and follows its file when it is edited.
+//! F2 steps through the files and then the built in NCBI codes.
+//!
+//! ```ron
+//! (
+//!     name: "Shouty Toki Pona",
+//!     // optional, start from a built in code and only list the differences
+//!     base: Some(TokiPona),
+//!     codons: { "UCC": 'S', "UCA": 'S' },
+//!     // optional with a base, its start codons are kept
+//!     starts: Some(["AUG", "GUG"]),
+//! )
+//! ```
+
+use std::collections::BTreeMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    parse::SequenceError,
+    translation::{NucleobaseRnaToAminoChar, TranslationMode},
+    Codon, CODON_TABLE,
+};
+use crate::{
+    game::{
+        assets::{CodonTableKey, HandleMap},
+        puzzle::in_free_play,
+    },
+    screen::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<CodonTable>();
+    app.init_asset_loader::<CodonTableLoader>();
+    app.init_resource::<ActiveCodonTable>();
+    app.add_systems(
+        Update,
+        // a level is judged in its own code, so it can't be switched mid-level
+        cycle_active_codon_table.run_if(in_state(Screen::Playing).and_then(in_free_play)),
+    );
+    app.add_systems(Update, sync_active_codon_table);
+}
+
+#[derive(Asset, TypePath, Debug, Clone, PartialEq)]
+pub struct CodonTable {
+    pub table: NucleobaseRnaToAminoChar,
+}
+
+/// The file layout, see the module docs.
+#[derive(Deserialize, Debug)]
+struct CodonTableFile {
+    name: String,
+    #[serde(default)]
+    base: Option<TranslationMode>,
+    #[serde(default)]
+    codons: BTreeMap<String, char>,
+    #[serde(default)]
+    starts: Option<Vec<String>>,
+}
+
+#[derive(Debug, Error)]
+pub enum CodonTableError {
+    #[error("{0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("\"{codon}\" is not a codon: {source}")]
+    InvalidCodon {
+        codon: String,
+        source: SequenceError,
+    },
+    #[error("{count} codons have no translation, list all 64 or give a base code")]
+    MissingCodons { count: usize },
+    #[error("no start codons, list some or give a base code")]
+    NoStarts,
+}
+
+fn parse_codon(s: &str) -> Result<Codon, CodonTableError> {
+    s.parse().map_err(|source| CodonTableError::InvalidCodon {
+        codon: s.to_string(),
+        source,
+    })
+}
+
+impl CodonTable {
+    pub fn parse(text: &str) -> Result<Self, CodonTableError> {
+        let file: CodonTableFile = ron::from_str(text)?;
+        let base = file.base.map(NucleobaseRnaToAminoChar::new);
+        let mut translations: [Option<char>; 64] = [None; 64];
+        if let Some(base) = &base {
+            for (i, translation) in translations.iter_mut().enumerate() {
+                *translation = Some(base.translation(&Codon::from_index(i)));
+            }
+        }
+        for (codon, c) in &file.codons {
+            translations[parse_codon(codon)?.to_index()] = Some(*c);
+        }
+        let count = translations.iter().filter(|c| c.is_none()).count();
+        if count > 0 {
+            return Err(CodonTableError::MissingCodons { count });
+        }
+        let starts = match (&file.starts, &base) {
+            (Some(starts), _) => starts
+                .iter()
+                .map(|s| parse_codon(s))
+                .collect::<Result<Vec<_>, _>>()?,
+            (None, Some(base)) => base.start_codons(),
+            (None, None) => Vec::new(),
+        };
+        if starts.is_empty() {
+            return Err(CodonTableError::NoStarts);
+        }
+        Ok(CodonTable {
+            table: NucleobaseRnaToAminoChar::custom(
+                file.name,
+                translations.map(|c| c.unwrap_or(' ')),
+                &starts,
+            ),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct CodonTableLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CodonTableLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read codon table as utf-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Could not parse codon table: {0}")]
+    CodonTable(#[from] CodonTableError),
+}
+
+impl AssetLoader for CodonTableLoader {
+    type Asset = CodonTable;
+    type Settings = ();
+    type Error = CodonTableLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(CodonTable::parse(&String::from_utf8(bytes)?)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["codons.ron"]
+    }
+}
+
+/// Where the active code comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodonTableSource {
+    /// A `.codons.ron` file, followed when it is edited.
+    File(CodonTableKey),
+    BuiltIn(TranslationMode),
+}
+
+impl CodonTableSource {
+    /// In the order F2 steps through them, the files and then the built in codes,
+    /// leaving out `Real` and `TokiPona` which have files.
+    pub fn all() -> Vec<CodonTableSource> {
+        let built_in = TranslationMode::ALL
+            .into_iter()
+            .filter(|mode| !matches!(mode, TranslationMode::Real | TranslationMode::TokiPona))
+            .map(CodonTableSource::BuiltIn);
+        CodonTableKey::ALL
+            .map(CodonTableSource::File)
+            .into_iter()
+            .chain(built_in)
+            .collect()
+    }
+}
+
+/// The code the game translates typed codons with, F2 switches to the next one.
+/// Until its file has loaded this is the built in [`CODON_TABLE`].
+#[derive(Resource, Debug, Clone)]
+pub struct ActiveCodonTable {
+    /// Picked with F2, used in free play.
+    pub source: CodonTableSource,
+    /// The code of the level being played, over `source` until the level ends.
+    pub level: Option<TranslationMode>,
+    pub table: NucleobaseRnaToAminoChar,
+}
+
+impl ActiveCodonTable {
+    /// Where `table` should come from, the level's code if there is one.
+    pub fn current_source(&self) -> CodonTableSource {
+        self.level
+            .map(CodonTableSource::BuiltIn)
+            .unwrap_or(self.source)
+    }
+}
+
+impl Default for ActiveCodonTable {
+    fn default() -> Self {
+        ActiveCodonTable {
+            source: CodonTableSource::File(CodonTableKey::TokiPona),
+            level: None,
+            table: CODON_TABLE,
+        }
+    }
+}
+
+fn cycle_active_codon_table(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut active: ResMut<ActiveCodonTable>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    let sources = CodonTableSource::all();
+    let index = sources
+        .iter()
+        .position(|source| *source == active.source)
+        .unwrap_or(0);
+    active.source = sources[(index + 1) % sources.len()];
+}
+
+/// Build the table when [`ActiveCodonTable::current_source`] changes,
+/// or copy the file's table in when it is (re)loaded.
+/// `synced` is the source the table was last built from, so a table written
+/// for a level isn't overwritten by the file of the F2 source.
+pub(crate) fn sync_active_codon_table(
+    mut asset_events: EventReader<AssetEvent<CodonTable>>,
+    codon_tables: Res<Assets<CodonTable>>,
+    codon_table_handles: Res<HandleMap<CodonTableKey>>,
+    mut active: ResMut<ActiveCodonTable>,
+    mut synced: Local<Option<CodonTableSource>>,
+) {
+    let source = active.current_source();
+    let key = match source {
+        CodonTableSource::File(key) => key,
+        CodonTableSource::BuiltIn(mode) => {
+            if *synced != Some(source) {
+                *synced = Some(source);
+                info!("translating with {}", mode.name());
+                active.table = NucleobaseRnaToAminoChar::new(mode);
+            }
+            return;
+        }
+    };
+    let Some(handle) = codon_table_handles.get(&key) else {
+        return;
+    };
+    let reloaded = asset_events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(handle) || event.is_modified(handle));
+    if !reloaded && *synced == Some(source) {
+        return;
+    }
+    let Some(codon_table) = codon_tables.get(handle) else {
+        return;
+    };
+    *synced = Some(source);
+    // only write on a real difference, so change detection isn't set every frame
+    if active.table != codon_table.table {
+        info!("translating with {}", codon_table.table.name());
+        active.table = codon_table.table.clone();
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_parse_with_base() {
+        let table =
+            CodonTable::parse(r#"(name: "Shouty", base: Some(TokiPona), codons: { "UCC": 'S' })"#)
+                .unwrap()
+                .table;
+        assert_eq!(table.name(), "Shouty");
+        assert_eq!(table.mode(), None);
+        assert_eq!(table.translation(&Codon::new('U', 'C', 'C')), 'S');
+        assert_eq!(table.translation(&Codon::new('A', 'U', 'G')), '^');
+        assert_eq!(table.start_codons(), CODON_TABLE.start_codons());
+    }
+    #[test]
+    fn test_asset_files_match_built_in() {
+        for (path, mode) in [
+            ("toki_pona", TranslationMode::TokiPona),
+            ("standard", TranslationMode::Real),
+        ] {
+            let text = std::fs::read_to_string(format!("assets/codes/{path}.codons.ron")).unwrap();
+            let table = CodonTable::parse(&text).unwrap().table;
+            let built_in = NucleobaseRnaToAminoChar::new(mode);
+            for i in 0..64 {
+                let codon = Codon::from_index(i);
+                assert_eq!(
+                    table.translation(&codon),
+                    built_in.translation(&codon),
+                    "{path} {codon}"
+                );
+                assert_eq!(table.is_start(&codon), built_in.is_start(&codon));
+            }
+        }
+    }
+    #[test]
+    fn test_sources() {
+        let sources = CodonTableSource::all();
+        assert_eq!(sources.len(), TranslationMode::ALL.len());
+        assert_eq!(
+            sources[..2],
+            [
+                CodonTableSource::File(CodonTableKey::TokiPona),
+                CodonTableSource::File(CodonTableKey::Standard)
+            ]
+        );
+        assert!(sources.contains(&CodonTableSource::BuiltIn(
+            TranslationMode::VertebrateMitochondrial
+        )));
+    }
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            CodonTable::parse(r#"(name: "x", codons: { "AUG": 'M' }, starts: Some(["AUG"]))"#),
+            Err(CodonTableError::MissingCodons { count: 63 })
+        ));
+        assert!(matches!(
+            CodonTable::parse(r#"(name: "x", base: Some(Real), codons: { "AUX": 'M' })"#),
+            Err(CodonTableError::InvalidCodon { .. })
+        ));
+        assert!(matches!(
+            CodonTable::parse(r#"(name: "x", base: Some(Real), starts: Some([]))"#),
+            Err(CodonTableError::NoStarts)
+        ));
+        assert!(matches!(
+            CodonTable::parse("(name: 3)"),
+            Err(CodonTableError::Ron(_))
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+use super::{Mrna, NucleobaseRna};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NucleobaseDna {
+    Adenine,
+    Thymine,
+    Cytosine,
+    Guanine,
+}
+
+impl NucleobaseDna {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'A' => Some(NucleobaseDna::Adenine),
+            'T' => Some(NucleobaseDna::Thymine),
+            'C' => Some(NucleobaseDna::Cytosine),
+            'G' => Some(NucleobaseDna::Guanine),
+            _ => None,
+        }
+    }
+    pub fn to_char(&self) -> char {
+        match self {
+            NucleobaseDna::Adenine => 'A',
+            NucleobaseDna::Thymine => 'T',
+            NucleobaseDna::Cytosine => 'C',
+            NucleobaseDna::Guanine => 'G',
+        }
+    }
+    /// The Watson-Crick partner of this base on the opposite strand.
+    pub fn complement(&self) -> Self {
+        match self {
+            NucleobaseDna::Adenine => NucleobaseDna::Thymine,
+            NucleobaseDna::Thymine => NucleobaseDna::Adenine,
+            NucleobaseDna::Cytosine => NucleobaseDna::Guanine,
+            NucleobaseDna::Guanine => NucleobaseDna::Cytosine,
+        }
+    }
+    /// The RNA base that RNA polymerase pairs with this base when it is read
+    /// off the template strand.
+    pub fn pair_rna(&self) -> NucleobaseRna {
+        match self {
+            NucleobaseDna::Adenine => NucleobaseRna::Uracil,
+            NucleobaseDna::Thymine => NucleobaseRna::Adenine,
+            NucleobaseDna::Cytosine => NucleobaseRna::Guanine,
+            NucleobaseDna::Guanine => NucleobaseRna::Cytosine,
+        }
+    }
+}
+
+/// A double stranded piece of DNA.
+/// Only the coding (sense) strand is stored, 5' to 3',
+/// the template strand is always derived from it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Dna {
+    coding: Vec<NucleobaseDna>,
+}
+
+impl Dna {
+    /// Build DNA from a literal coding strand, panics on anything [`str::parse`] would reject.
+    pub fn new(s: &str) -> Self {
+        s.parse()
+            .unwrap_or_else(|e| panic!("invalid DNA {s:?}: {e}"))
+    }
+    pub fn from_bases(coding: Vec<NucleobaseDna>) -> Self {
+        Dna { coding }
+    }
+    pub fn len(&self) -> usize {
+        self.coding.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.coding.is_empty()
+    }
+    pub fn coding_strand(&self) -> &[NucleobaseDna] {
+        &self.coding
+    }
+    /// The template (antisense) strand, base for base opposite the coding strand,
+    /// so it reads 3' to 5'.
+    pub fn template_strand(&self) -> Vec<NucleobaseDna> {
+        self.coding.iter().map(NucleobaseDna::complement).collect()
+    }
+    /// Swap the strands without changing direction, so the result reads 3' to 5'.
+    pub fn complement(&self) -> Self {
+        Dna {
+            coding: self.template_strand(),
+        }
+    }
+    /// The opposite strand read 5' to 3', i.e. the same molecule flipped over.
+    pub fn reverse_complement(&self) -> Self {
+        Dna {
+            coding: self
+                .coding
+                .iter()
+                .rev()
+                .map(NucleobaseDna::complement)
+                .collect(),
+        }
+    }
+    /// Transcribe the template strand into an mRNA.
+    /// The mRNA has the same sequence as the coding strand with U in place of T.
+    /// Trailing bases that don't fill a codon are dropped.
+    pub fn transcribe(&self) -> Mrna {
+        Mrna::from_bases(&self.transcribe_bases())
+    }
+    /// The full transcript as loose bases, including any partial codon at the end.
+    pub fn transcribe_bases(&self) -> Vec<NucleobaseRna> {
+        self.template_strand()
+            .iter()
+            .map(NucleobaseDna::pair_rna)
+            .collect()
+    }
+    pub fn to_string_bases(&self) -> String {
+        self.coding.iter().map(NucleobaseDna::to_char).collect()
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_transcribe() {
+        let dna = Dna::new("ATGACTCAGCGAATAGTTCCTCAGAACGCGTGA");
+        assert_eq!(
+            dna.transcribe(),
+            Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA")
+        );
+        assert_eq!(dna.transcribe().to_string_toki(), "^Toki Pona.");
+    }
+    #[test]
+    fn test_complements() {
+        let dna = Dna::new("ATGCCA");
+        assert_eq!(dna.complement().to_string_bases(), "TACGGT");
+        assert_eq!(dna.reverse_complement().to_string_bases(), "TGGCAT");
+        assert_eq!(dna.reverse_complement().reverse_complement(), dna);
+    }
+    #[test]
+    fn test_transcribe_drops_partial_codon() {
+        let dna = Dna::new("ATGAC");
+        assert_eq!(dna.transcribe(), Mrna::new("AUG"));
+    }
+}
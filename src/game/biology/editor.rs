@@ -0,0 +1,408 @@
+//! A text editor view of the selected mRNA, typed bases included, with a cursor and selection.
+//! Comma and Period move left and right (Ctrl by a codon, Shift selects), the arrows are left
+//! to walking. Home and End jump, typing inserts at the cursor, Backspace and Delete remove,
+//! Ctrl+A C X V select all, copy, cut and paste.
+//! With the cursor at the end typing appends through [`super::build_codon`] as before.
+//! An edit that finishes a product releases it, as typing its stop codon would.
+//! Codons alternate in brightness, bases inside an open reading frame are colored by its frame.
+
+use std::ops::Range;
+
+use bevy::{
+    color::palettes::css::{GRAY, LIME, ORANGE, RED, VIOLET, WHITE, YELLOW},
+    prelude::*,
+};
+
+use super::{
+    codon_table::ActiveCodonTable,
+    history::{Edit, EditHistory, HistorySettings},
+    orf::find_orfs,
+    parse::parse_rna_bases,
+    release_new_products,
+    translation::NucleobaseRnaToAminoChar,
+    AllowedBases, CodonState, LogTextRes, Mrna, NucleobaseRna, SelectedMrna,
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BaseClipboard>();
+    app.add_systems(OnEnter(Screen::Playing), spawn_sequence_view);
+    app.add_systems(
+        Update,
+        update_sequence_view.run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Colors for bases in an ORF of frame 0, 1 and 2.
+const FRAME_COLORS: [Srgba; 3] = [LIME, ORANGE, VIOLET];
+/// Every other codon is drawn this much dimmer, to show where codons begin.
+const ODD_CODON_ALPHA: f32 = 0.6;
+
+/// Cursor and selection over the bases of an mRNA, followed by its typed bases.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EditorCursor {
+    /// `None` follows the end of the sequence.
+    position: Option<usize>,
+    /// The other end of the selection.
+    anchor: Option<usize>,
+}
+
+impl EditorCursor {
+    pub fn at(position: usize) -> Self {
+        EditorCursor {
+            position: Some(position),
+            anchor: None,
+        }
+    }
+    /// At the end with nothing selected, typing appends.
+    pub fn is_appending(&self) -> bool {
+        self.position.is_none() && self.anchor.is_none()
+    }
+    pub fn position(&self, len: usize) -> usize {
+        self.position.unwrap_or(len).min(len)
+    }
+    pub fn selection(&self, len: usize) -> Option<Range<usize>> {
+        let anchor = self.anchor?.min(len);
+        let position = self.position(len);
+        let range = anchor.min(position)..anchor.max(position);
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// Move to `position`, selecting from where the cursor was when `extend`.
+    pub fn move_to(&mut self, position: usize, len: usize, extend: bool) {
+        let from = self.position(len);
+        let position = position.min(len);
+        self.anchor = match (extend, self.anchor) {
+            (true, None) => Some(from),
+            (true, anchor) => anchor,
+            (false, _) => None,
+        };
+        self.position = Some(position);
+        self.settle(len);
+    }
+
+    /// Put `inserted` in place of the selection or at the cursor, leaving the cursor after it.
+    pub fn insert(
+        &mut self,
+        sequence: &[NucleobaseRna],
+        inserted: &[NucleobaseRna],
+    ) -> Vec<NucleobaseRna> {
+        let range = self.selection(sequence.len()).unwrap_or_else(|| {
+            let position = self.position(sequence.len());
+            position..position
+        });
+        let mut edited = sequence.to_vec();
+        edited.splice(range.clone(), inserted.iter().copied());
+        self.position = Some(range.start + inserted.len());
+        self.anchor = None;
+        self.settle(edited.len());
+        edited
+    }
+
+    /// Remove the selection, or the base before (after, when `forward`) the cursor.
+    /// `None` when there is nothing to remove.
+    pub fn delete(
+        &mut self,
+        sequence: &[NucleobaseRna],
+        forward: bool,
+    ) -> Option<Vec<NucleobaseRna>> {
+        let len = sequence.len();
+        let position = self.position(len);
+        let range = match self.selection(len) {
+            Some(range) => range,
+            None if forward && position < len => position..position + 1,
+            None if !forward && position > 0 => position - 1..position,
+            None => return None,
+        };
+        let mut edited = sequence.to_vec();
+        edited.drain(range.clone());
+        self.position = Some(range.start);
+        self.anchor = None;
+        self.settle(edited.len());
+        Some(edited)
+    }
+
+    /// Back to following the end once the cursor gets there with nothing selected.
+    fn settle(&mut self, len: usize) {
+        if self.anchor.is_none() && self.position.is_some_and(|p| p >= len) {
+            self.position = None;
+        }
+    }
+}
+
+/// What Ctrl+C and Ctrl+X copied, as a string of bases.
+#[derive(Resource, Debug, Default)]
+pub struct BaseClipboard(pub String);
+
+/// The bases of `mrna` followed by the ones typed since its last codon.
+pub fn sequence(mrna: &Mrna, codon_state: &CodonState) -> Vec<NucleobaseRna> {
+    let mut bases = mrna.bases();
+    bases.extend(&codon_state.bases);
+    bases
+}
+
+pub fn edit_sequence(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<HistorySettings>,
+    allowed_bases: Res<AllowedBases>,
+    active_table: Res<ActiveCodonTable>,
+    mut clipboard: ResMut<BaseClipboard>,
+    mut mrna_q: Query<
+        (
+            Entity,
+            &mut Mrna,
+            &mut CodonState,
+            &mut EditHistory,
+            &mut EditorCursor,
+        ),
+        With<SelectedMrna>,
+    >,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    let Ok((entity, mut mrna, mut codon_state, mut history, mut cursor)) = mrna_q.get_single_mut()
+    else {
+        return;
+    };
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let before = sequence(&mrna, &codon_state);
+    let len = before.len();
+    let position = cursor.position(len);
+    let step = if ctrl { 3 } else { 1 };
+
+    let mut after = None;
+    if keyboard_input.just_pressed(KeyCode::Comma) {
+        cursor.move_to(position.saturating_sub(step), len, shift);
+    } else if keyboard_input.just_pressed(KeyCode::Period) {
+        cursor.move_to(position + step, len, shift);
+    } else if keyboard_input.just_pressed(KeyCode::Home) {
+        cursor.move_to(0, len, shift);
+    } else if keyboard_input.just_pressed(KeyCode::End) {
+        cursor.move_to(len, len, shift);
+    } else if ctrl && keyboard_input.just_pressed(KeyCode::KeyA) {
+        cursor.move_to(0, len, false);
+        cursor.move_to(len, len, true);
+    } else if ctrl
+        && (keyboard_input.just_pressed(KeyCode::KeyC)
+            || keyboard_input.just_pressed(KeyCode::KeyX))
+    {
+        if let Some(range) = cursor.selection(len) {
+            clipboard.0 = before[range].iter().map(|b| b.to_char()).collect();
+            if keyboard_input.just_pressed(KeyCode::KeyX) {
+                after = cursor.delete(&before, false);
+            }
+        }
+    } else if ctrl && keyboard_input.just_pressed(KeyCode::KeyV) {
+        match parse_rna_bases(clipboard.0.trim()) {
+            Ok(bases) => match bases.iter().find(|b| !allowed_bases.0.contains(b)) {
+                Some(base) => {
+                    log_text.text = format!(
+                        "{}\nCan't paste: {} isn't allowed",
+                        log_text.text,
+                        base.to_char()
+                    )
+                }
+                None if !bases.is_empty() => after = Some(cursor.insert(&before, &bases)),
+                None => {}
+            },
+            Err(e) => log_text.text = format!("{}\nCan't paste: {e}", log_text.text),
+        }
+    } else if keyboard_input.just_pressed(KeyCode::Delete) {
+        after = cursor.delete(&before, true);
+    } else if keyboard_input.just_pressed(KeyCode::Backspace) && !cursor.is_appending() {
+        after = cursor.delete(&before, false);
+    } else if !ctrl && !cursor.is_appending() {
+        for (key, base) in [
+            (KeyCode::KeyU, NucleobaseRna::Uracil),
+            (KeyCode::KeyC, NucleobaseRna::Cytosine),
+            (KeyCode::KeyA, NucleobaseRna::Adenine),
+            (KeyCode::KeyG, NucleobaseRna::Guanine),
+        ] {
+            if keyboard_input.just_pressed(key) && allowed_bases.0.contains(&base) {
+                after = Some(cursor.insert(&before, &[base]));
+                break;
+            }
+        }
+    }
+    if let Some(after) = after {
+        let unedited = mrna.clone();
+        history.perform(
+            vec![Edit::Replace { before, after }],
+            &mut mrna,
+            &mut codon_state,
+            settings.limit,
+        );
+        if release_new_products(&mut commands, entity, &unedited, &mrna, &active_table.table) {
+            history.mark_translated();
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SequenceView;
+
+fn spawn_sequence_view(mut commands: Commands) {
+    commands.spawn((
+        Name::new("SequenceView"),
+        SequenceView,
+        TextBundle::from_sections([]).with_style(Style {
+            position_type: PositionType::Absolute,
+            // the right half, the codon chart is on the left
+            bottom: Val::Px(20.0),
+            left: Val::Percent(50.0),
+            right: Val::Px(20.0),
+            ..default()
+        }),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn update_sequence_view(
+    selected_q: Query<
+        (
+            Ref<Mrna>,
+            Ref<CodonState>,
+            Ref<EditorCursor>,
+            Ref<SelectedMrna>,
+        ),
+        With<SelectedMrna>,
+    >,
+    active_table: Res<ActiveCodonTable>,
+    mut view_q: Query<&mut Text, With<SequenceView>>,
+    added_q: Query<(), Added<SequenceView>>,
+) {
+    let Ok((mrna, codon_state, cursor, selected)) = selected_q.get_single() else {
+        return;
+    };
+    if !mrna.is_changed()
+        && !codon_state.is_changed()
+        && !cursor.is_changed()
+        && !selected.is_added()
+        && !active_table.is_changed()
+        && added_q.is_empty()
+    {
+        return;
+    }
+    let sections = view_sections(&sequence(&mrna, &codon_state), &cursor, &active_table.table);
+    for mut text in &mut view_q {
+        text.sections = sections.clone();
+    }
+}
+
+/// One section per base, a space between codons and a bar for the cursor.
+fn view_sections(
+    bases: &[NucleobaseRna],
+    cursor: &EditorCursor,
+    table: &NucleobaseRnaToAminoChar,
+) -> Vec<TextSection> {
+    let style = |color: Color| TextStyle {
+        font_size: 24.,
+        color,
+        ..default()
+    };
+    let orfs = find_orfs(bases, table);
+    let selection = cursor.selection(bases.len());
+    let position = cursor.position(bases.len());
+    let mut sections = Vec::with_capacity(bases.len() * 2 + 1);
+    for (i, base) in bases.iter().enumerate() {
+        if i == position {
+            sections.push(TextSection::new("|", style(RED.into())));
+        } else if i > 0 && i % 3 == 0 {
+            sections.push(TextSection::new(" ", style(WHITE.into())));
+        }
+        let color = if selection.as_ref().is_some_and(|s| s.contains(&i)) {
+            YELLOW
+        } else {
+            let frame = orfs
+                .iter()
+                .find(|orf| (orf.start..orf.end()).contains(&i))
+                .map(|orf| orf.frame);
+            let color = frame.map_or(GRAY, |frame| FRAME_COLORS[frame]);
+            if (i / 3) % 2 == 1 {
+                color.with_alpha(ODD_CODON_ALPHA)
+            } else {
+                color
+            }
+        };
+        sections.push(TextSection::new(
+            base.to_char().to_string(),
+            style(color.into()),
+        ));
+    }
+    if position == bases.len() {
+        sections.push(TextSection::new("|", style(RED.into())));
+    }
+    sections
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn bases(s: &str) -> Vec<NucleobaseRna> {
+        parse_rna_bases(s).unwrap()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut cursor = EditorCursor::at(3);
+        let edited = cursor.insert(&bases("AUGUAA"), &bases("ACU"));
+        assert_eq!(edited, bases("AUGACUUAA"));
+        assert_eq!(cursor.position(edited.len()), 6);
+        assert!(!cursor.is_appending());
+        // reaching the end goes back to appending
+        let mut cursor = EditorCursor::at(6);
+        cursor.insert(&bases("AUGACU"), &bases("U"));
+        assert!(cursor.is_appending());
+    }
+
+    #[test]
+    fn test_select_and_delete() {
+        let sequence = bases("AUGACUUAA");
+        let mut cursor = EditorCursor::at(3);
+        cursor.move_to(6, sequence.len(), true);
+        assert_eq!(cursor.selection(sequence.len()), Some(3..6));
+        // typing replaces the selection
+        let mut replaced = cursor;
+        assert_eq!(
+            replaced.insert(&sequence, &bases("CCU")),
+            bases("AUGCCUUAA")
+        );
+        assert_eq!(cursor.delete(&sequence, false), Some(bases("AUGUAA")));
+        assert_eq!(cursor.selection(6), None);
+        assert_eq!(cursor.position(6), 3);
+        assert_eq!(cursor.delete(&bases("AUGUAA"), true), Some(bases("AUGAA")));
+        assert_eq!(cursor.delete(&bases("AUGAA"), false), Some(bases("AUAA")));
+        let mut cursor = EditorCursor::at(0);
+        assert_eq!(cursor.delete(&sequence, false), None);
+        // selecting backwards
+        cursor.move_to(5, sequence.len(), false);
+        cursor.move_to(2, sequence.len(), true);
+        assert_eq!(cursor.selection(sequence.len()), Some(2..5));
+    }
+
+    #[test]
+    fn test_replace_keeps_partial_codon() {
+        let mut mrna = Mrna::new("AUGACU");
+        let mut codon_state = CodonState::default();
+        codon_state.push(NucleobaseRna::Uracil);
+        let before = sequence(&mrna, &codon_state);
+        let after = EditorCursor::at(3).insert(&before, &bases("C"));
+        let mut history = EditHistory::default();
+        history.perform(
+            vec![Edit::Replace {
+                before: before.clone(),
+                after,
+            }],
+            &mut mrna,
+            &mut codon_state,
+            10,
+        );
+        assert_eq!(mrna, Mrna::new("AUGCAC"));
+        assert_eq!(sequence(&mrna, &codon_state), bases("AUGCACUU"));
+        history.undo(&mut mrna, &mut codon_state);
+        assert_eq!(sequence(&mrna, &codon_state), before);
+    }
+}
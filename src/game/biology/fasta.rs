@@ -0,0 +1,253 @@
+//! Read and write [FASTA](https://en.wikipedia.org/wiki/FASTA_format) sequence files.
+//! `.fasta` / `.fa` files under `assets/` load as [`Fasta`] assets.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use thiserror::Error;
+
+use super::{
+    ambiguity::{definite_bases, AmbiguousBase, AmbiguousTranslation},
+    packed::PackedBases,
+    translation::NucleobaseRnaToAminoChar,
+    Mrna,
+};
+
+/// Bases per line when writing, the usual NCBI width.
+pub const FASTA_LINE_WIDTH: usize = 70;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Fasta>();
+    app.init_asset_loader::<FastaLoader>();
+}
+
+/// One `>header` and the sequence below it, which may use IUPAC ambiguity codes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FastaRecord {
+    pub header: String,
+    pub bases: Vec<AmbiguousBase>,
+}
+
+impl FastaRecord {
+    pub fn new(header: impl Into<String>, mrna: Mrna) -> Self {
+        Self {
+            header: header.into(),
+            bases: mrna.bases().into_iter().map(AmbiguousBase::from).collect(),
+        }
+    }
+    /// The first word of the header, the usual FASTA identifier.
+    pub fn id(&self) -> &str {
+        self.header.split_whitespace().next().unwrap_or("")
+    }
+    /// The sequence as an mRNA, `None` if any base is ambiguous.
+    /// A trailing partial codon is dropped, like [`Mrna::from_bases`].
+    pub fn mrna(&self) -> Option<Mrna> {
+        definite_bases(&self.bases).map(|bases| Mrna::from_bases(&bases))
+    }
+    /// The whole sequence two bits a base, for genome sized records.
+    /// `None` if any base is ambiguous.
+    pub fn packed(&self) -> Option<PackedBases> {
+        self.bases.iter().map(AmbiguousBase::definite).collect()
+    }
+    /// Codon by codon, with every character an ambiguous codon could stand for.
+    pub fn translate(&self, table: &NucleobaseRnaToAminoChar) -> Vec<AmbiguousTranslation> {
+        table.translate_ambiguous_bases(&self.bases)
+    }
+}
+
+/// All the records of one FASTA file.
+#[derive(Asset, TypePath, Debug, Clone, PartialEq, Default)]
+pub struct Fasta {
+    pub records: Vec<FastaRecord>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FastaError {
+    #[error("line {line}: sequence data before the first '>' header")]
+    MissingHeader { line: usize },
+    #[error("line {line}, column {column}: '{c}' is not a nucleobase")]
+    InvalidBase { line: usize, column: usize, c: char },
+}
+
+impl Fasta {
+    /// Parse FASTA text.
+    /// Bases may be upper or lower case IUPAC codes and DNA `T` is read as `U`.
+    /// Whitespace inside sequence lines, blank lines and `;` comment lines are skipped.
+    pub fn parse(text: &str) -> Result<Self, FastaError> {
+        let mut records = Vec::new();
+        let mut current: Option<(String, Vec<AmbiguousBase>)> = None;
+        for (line_index, line) in text.lines().enumerate() {
+            let line_number = line_index + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with(';') {
+                continue;
+            }
+            if let Some(header) = trimmed.strip_prefix('>') {
+                if let Some((header, bases)) = current.take() {
+                    records.push(FastaRecord { header, bases });
+                }
+                current = Some((header.trim().to_string(), Vec::new()));
+                continue;
+            }
+            let Some((_, bases)) = current.as_mut() else {
+                return Err(FastaError::MissingHeader { line: line_number });
+            };
+            for (column_index, c) in line.chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                match AmbiguousBase::from_char(c) {
+                    Some(base) => bases.push(base),
+                    None => {
+                        return Err(FastaError::InvalidBase {
+                            line: line_number,
+                            column: column_index + 1,
+                            c,
+                        })
+                    }
+                }
+            }
+        }
+        if let Some((header, bases)) = current {
+            records.push(FastaRecord { header, bases });
+        }
+        Ok(Fasta { records })
+    }
+
+    /// The first record with this [`FastaRecord::id`].
+    pub fn record(&self, id: &str) -> Option<&FastaRecord> {
+        self.records.iter().find(|record| record.id() == id)
+    }
+
+    /// Write the records back out, wrapping sequence lines at `line_width` bases.
+    pub fn to_string_wrapped(&self, line_width: usize) -> String {
+        let mut s = String::new();
+        for record in &self.records {
+            s.push('>');
+            s.push_str(&record.header);
+            s.push('\n');
+            let bases: Vec<char> = record.bases.iter().map(AmbiguousBase::to_char).collect();
+            for line in bases.chunks(line_width.max(1)) {
+                s.extend(line);
+                s.push('\n');
+            }
+        }
+        s
+    }
+}
+
+impl std::fmt::Display for Fasta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_string_wrapped(FASTA_LINE_WIDTH))
+    }
+}
+
+#[derive(Default)]
+pub struct FastaLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum FastaLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read FASTA as utf-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Could not parse FASTA: {0}")]
+    Fasta(#[from] FastaError),
+}
+
+impl AssetLoader for FastaLoader {
+    type Asset = Fasta;
+    type Settings = ();
+    type Error = FastaLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(Fasta::parse(&String::from_utf8(bytes)?)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fasta", "fa"]
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::translation::TranslationMode;
+    #[test]
+    fn test_parse_multi_record() {
+        let fasta = Fasta::parse(
+            ">toki pona\nAUGACUCAGCGA\nauag uuccucagaac\n; comment\nGCGUGA\n\n>second\nATGTAA\n",
+        )
+        .unwrap();
+        assert_eq!(fasta.records.len(), 2);
+        assert_eq!(fasta.records[0].header, "toki pona");
+        assert_eq!(
+            fasta.records[0].mrna().unwrap().to_string_toki(),
+            "^Toki Pona."
+        );
+        assert_eq!(fasta.records[1].mrna(), Some(Mrna::new("AUGUAA")));
+        assert_eq!(fasta.record("toki"), Some(&fasta.records[0]));
+        assert_eq!(fasta.record("pona"), None);
+    }
+    #[test]
+    fn test_packed() {
+        let fasta =
+            Fasta::parse(">long\nAUGACUCAGCGA\nAUAGUUCCUCAGAACGCGUGAAU\n>n\nAUGN\n").unwrap();
+        let packed = fasta.records[0].packed().unwrap();
+        // the trailing AU is kept, unlike in the mRNA
+        assert_eq!(packed.len(), 35);
+        assert_eq!(packed.to_string(), "AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGAAU");
+        assert_eq!(Some(packed.to_mrna()), fasta.records[0].mrna());
+        assert_eq!(fasta.records[1].packed(), None);
+    }
+    #[test]
+    fn test_ambiguity_codes() {
+        let fasta = Fasta::parse(">partly known\nATGGCNtty\n").unwrap();
+        let record = &fasta.records[0];
+        assert_eq!(record.mrna(), None);
+        let table = NucleobaseRnaToAminoChar::new(TranslationMode::Real);
+        let translation: Vec<String> = record
+            .translate(&table)
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(translation, ["M", "A", "F"]);
+        assert_eq!(fasta.to_string(), ">partly known\nAUGGCNUUY\n");
+    }
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Fasta::parse("AUG\n"),
+            Err(FastaError::MissingHeader { line: 1 })
+        );
+        assert_eq!(
+            Fasta::parse(">x\nAUG\nAXG\n"),
+            Err(FastaError::InvalidBase {
+                line: 3,
+                column: 2,
+                c: 'X'
+            })
+        );
+    }
+    #[test]
+    fn test_round_trip() {
+        let fasta = Fasta {
+            records: vec![FastaRecord::new(
+                "toki",
+                Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA"),
+            )],
+        };
+        let text = fasta.to_string_wrapped(12);
+        assert_eq!(text, ">toki\nAUGACUCAGCGA\nAUAGUUCCUCAG\nAACGCGUGA\n");
+        assert_eq!(Fasta::parse(&text), Ok(fasta));
+    }
+}
@@ -0,0 +1,364 @@
+//! The [HP model](https://en.wikipedia.org/wiki/Hydrophobic-polar_protein_folding_model):
+//! residues are only hydrophobic or polar, the chain is a self-avoiding walk on a square
+//! lattice, and every pair of hydrophobic residues that touch without being bonded lowers the
+//! energy by one. A released chain of real amino acids is folded by simulated annealing and
+//! shown above the cell that made it, hydrophobic residues orange and polar ones blue.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    color::palettes::css::{LIGHT_GRAY, ORANGE, ROYAL_BLUE},
+    prelude::*,
+    render::view::visibility::RenderLayers,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    protein::{AminoAcid, Polypeptide},
+    LogTextRes, Mrna, ProductReleased,
+};
+use crate::screen::Screen;
+
+/// Lattice spacing in world units.
+const SPACING: f32 = 1.2;
+/// Where a fold sits relative to its cell.
+const FOLD_OFFSET: Vec3 = Vec3::new(0.0, 7.0, 0.0);
+/// Short chains settle long before [`FoldSettings::steps`], they get this many per residue.
+const MAX_STEPS_PER_RESIDUE: usize = 1_000;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FoldSettings>();
+    app.init_resource::<FoldSettings>();
+    app.observe(fold_released_chain);
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct FoldSettings {
+    /// Monte Carlo moves tried per fold, fewer for short chains.
+    pub steps: usize,
+    pub start_temperature: f32,
+    pub end_temperature: f32,
+    /// The same chain always folds the same way for the same seed.
+    pub seed: u64,
+}
+
+impl Default for FoldSettings {
+    fn default() -> Self {
+        Self {
+            steps: 20_000,
+            start_temperature: 2.0,
+            end_temperature: 0.05,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hp {
+    Hydrophobic,
+    Polar,
+}
+
+impl AminoAcid {
+    /// Hydrophobic for a positive Kyte-Doolittle hydropathy: I, V, L, F, C, M and A.
+    pub fn hp(&self) -> Hp {
+        if self.hydropathy() > 0.0 {
+            Hp::Hydrophobic
+        } else {
+            Hp::Polar
+        }
+    }
+}
+
+/// Parse `H` and `P` letters, `None` for anything else.
+pub fn parse_hp(s: &str) -> Option<Vec<Hp>> {
+    s.chars()
+        .map(|c| match c {
+            'H' => Some(Hp::Hydrophobic),
+            'P' => Some(Hp::Polar),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How the chain bends at a residue, relative to the bond coming in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+const TURNS: [Turn; 3] = [Turn::Left, Turn::Straight, Turn::Right];
+
+/// Lattice points of the walk starting along +x, `None` if it runs into itself.
+fn walk(turns: &[Turn]) -> Option<Vec<IVec2>> {
+    let mut positions = Vec::new();
+    walk_into(turns, &mut positions, &mut HashSet::new()).then_some(positions)
+}
+
+/// [`walk`] into `positions`, `false` if it runs into itself.
+/// Both buffers are cleared first, so the annealing loop can reuse them.
+fn walk_into(turns: &[Turn], positions: &mut Vec<IVec2>, occupied: &mut HashSet<IVec2>) -> bool {
+    positions.clear();
+    positions.extend([IVec2::ZERO, IVec2::X]);
+    occupied.clear();
+    occupied.extend(positions.iter().copied());
+    let mut direction = IVec2::X;
+    let mut last = IVec2::X;
+    for turn in turns {
+        direction = match turn {
+            Turn::Left => direction.perp(),
+            Turn::Straight => direction,
+            Turn::Right => -direction.perp(),
+        };
+        last += direction;
+        if !occupied.insert(last) {
+            return false;
+        }
+        positions.push(last);
+    }
+    true
+}
+
+/// Minus one for each pair of hydrophobic residues on neighbouring points that aren't bonded.
+pub fn energy(hp: &[Hp], positions: &[IVec2]) -> i32 {
+    energy_with(hp, positions, &mut HashMap::new())
+}
+
+/// [`energy`] with a reused map from lattice point to residue.
+fn energy_with(hp: &[Hp], positions: &[IVec2], index: &mut HashMap<IVec2, usize>) -> i32 {
+    index.clear();
+    index.extend(positions.iter().enumerate().map(|(i, p)| (*p, i)));
+    let mut contacts = 0;
+    for (i, position) in positions.iter().enumerate() {
+        if hp[i] != Hp::Hydrophobic {
+            continue;
+        }
+        // +x and +y only, so each pair is counted once
+        for step in [IVec2::X, IVec2::Y] {
+            if let Some(j) = index.get(&(*position + step)) {
+                if hp[*j] == Hp::Hydrophobic && i.abs_diff(*j) > 1 {
+                    contacts += 1;
+                }
+            }
+        }
+    }
+    -contacts
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    /// One lattice point per residue, bonded residues are neighbours.
+    pub positions: Vec<IVec2>,
+    pub energy: i32,
+}
+
+/// Search for a low energy fold by simulated annealing, changing one turn at a time.
+pub fn fold(hp: &[Hp], settings: &FoldSettings) -> Fold {
+    let mut turns = vec![Turn::Straight; hp.len().saturating_sub(2)];
+    let mut positions = walk(&turns).unwrap();
+    positions.truncate(hp.len());
+    let mut current = energy(hp, &positions);
+    let mut best = Fold {
+        positions,
+        energy: current,
+    };
+    if turns.is_empty() {
+        return best;
+    }
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let cooling = (settings.end_temperature / settings.start_temperature).max(f32::EPSILON);
+    let steps = settings.steps.min(hp.len() * MAX_STEPS_PER_RESIDUE);
+    let mut positions = Vec::with_capacity(hp.len());
+    let mut occupied = HashSet::with_capacity(hp.len());
+    let mut index = HashMap::with_capacity(hp.len());
+    for step in 0..steps {
+        let temperature = settings.start_temperature * cooling.powf(step as f32 / steps as f32);
+        let k = rng.gen_range(0..turns.len());
+        let old = turns[k];
+        turns[k] = TURNS[(TURNS.iter().position(|t| *t == old).unwrap() + rng.gen_range(1..3)) % 3];
+        if !walk_into(&turns, &mut positions, &mut occupied) {
+            turns[k] = old;
+            continue;
+        }
+        let candidate = energy_with(hp, &positions, &mut index);
+        let delta = (candidate - current) as f32;
+        if delta <= 0.0 || rng.gen::<f32>() < (-delta / temperature).exp() {
+            current = candidate;
+            if current < best.energy {
+                best = Fold {
+                    positions: positions.clone(),
+                    energy: current,
+                };
+            }
+        } else {
+            turns[k] = old;
+        }
+    }
+    best
+}
+
+/// The fold on screen for a cell, replaced when the cell releases another chain.
+#[derive(Component, Debug)]
+pub struct FoldView {
+    cell: Entity,
+}
+
+fn fold_released_chain(
+    trigger: Trigger<ProductReleased>,
+    mut commands: Commands,
+    settings: Res<FoldSettings>,
+    parent_q: Query<&Parent>,
+    transform_q: Query<&GlobalTransform>,
+    view_q: Query<(Entity, &FoldView)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    let event = trigger.event();
+    if !event.table.is_amino_acid() {
+        return;
+    }
+    let product = &event.product;
+    let message = Mrna::from_bases(&event.mrna.bases()[product.start * 3..product.end * 3]);
+    let Some(chain) = Polypeptide::translate_with(&message, &event.table) else {
+        return;
+    };
+    let hp: Vec<Hp> = chain.residues.iter().map(AminoAcid::hp).collect();
+    if hp.is_empty() {
+        return;
+    }
+    let fold = fold(&hp, &settings);
+    let hp_string: String = hp
+        .iter()
+        .map(|h| match h {
+            Hp::Hydrophobic => 'H',
+            Hp::Polar => 'P',
+        })
+        .collect();
+    log_text.text = format!(
+        "{}\n{} folds with energy {}",
+        log_text.text, hp_string, fold.energy
+    );
+
+    let cell = parent_q.get(event.entity).map_or(event.entity, Parent::get);
+    for (entity, view) in &view_q {
+        if view.cell == cell {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let origin = transform_q
+        .get(cell)
+        .map_or(Vec3::ZERO, GlobalTransform::translation)
+        + FOLD_OFFSET;
+    // centred on the origin
+    let center = fold
+        .positions
+        .iter()
+        .fold(Vec2::ZERO, |sum, p| sum + p.as_vec2())
+        / fold.positions.len() as f32;
+    let point = |p: IVec2| ((p.as_vec2() - center) * SPACING).extend(0.0);
+    let sphere = meshes.add(Sphere::new(0.4));
+    let bond = meshes.add(Cuboid::new(SPACING, 0.12, 0.12));
+    let hydrophobic = materials.add(Color::from(ORANGE));
+    let polar = materials.add(Color::from(ROYAL_BLUE));
+    let bond_material = materials.add(Color::from(LIGHT_GRAY));
+    commands
+        .spawn((
+            Name::new(format!("Fold {}", chain)),
+            FoldView { cell },
+            SpatialBundle::from_transform(Transform::from_translation(origin)),
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|parent| {
+            for (i, position) in fold.positions.iter().enumerate() {
+                parent.spawn((
+                    Name::new(chain.residues[i].three_letter_code()),
+                    PbrBundle {
+                        mesh: sphere.clone(),
+                        material: match hp[i] {
+                            Hp::Hydrophobic => hydrophobic.clone(),
+                            Hp::Polar => polar.clone(),
+                        },
+                        transform: Transform::from_translation(point(*position)),
+                        ..default()
+                    },
+                    RenderLayers::layer(1),
+                ));
+            }
+            for pair in fold.positions.windows(2) {
+                let (a, b) = (point(pair[0]), point(pair[1]));
+                let rotation = if pair[0].x == pair[1].x {
+                    Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)
+                } else {
+                    Quat::IDENTITY
+                };
+                parent.spawn((
+                    Name::new("bond"),
+                    PbrBundle {
+                        mesh: bond.clone(),
+                        material: bond_material.clone(),
+                        transform: Transform::from_translation((a + b) / 2.0)
+                            .with_rotation(rotation),
+                        ..default()
+                    },
+                    RenderLayers::layer(1),
+                ));
+            }
+        });
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn is_walk(positions: &[IVec2]) -> bool {
+        let distinct: HashSet<&IVec2> = positions.iter().collect();
+        distinct.len() == positions.len()
+            && positions
+                .windows(2)
+                .all(|pair| (pair[0] - pair[1]).abs().element_sum() == 1)
+    }
+
+    #[test]
+    fn test_hp() {
+        let chain = Polypeptide::from_one_letter("MKVLE").unwrap();
+        let hp: Vec<Hp> = chain.residues.iter().map(AminoAcid::hp).collect();
+        assert_eq!(hp, parse_hp("HPHHP").unwrap());
+        assert_eq!(parse_hp("HXP"), None);
+    }
+
+    #[test]
+    fn test_energy() {
+        let hp = parse_hp("HPPH").unwrap();
+        let square = [IVec2::ZERO, IVec2::X, IVec2::ONE, IVec2::Y];
+        assert_eq!(energy(&hp, &square), -1);
+        let line = [IVec2::ZERO, IVec2::X, IVec2::new(2, 0), IVec2::new(3, 0)];
+        assert_eq!(energy(&hp, &line), 0);
+        // bonded neighbours don't count
+        assert_eq!(
+            energy(&parse_hp("HH").unwrap(), &[IVec2::ZERO, IVec2::X]),
+            0
+        );
+        assert_eq!(walk(&[Turn::Left, Turn::Left, Turn::Left]), None);
+    }
+
+    #[test]
+    fn test_fold() {
+        let settings = FoldSettings::default();
+        let square = fold(&parse_hp("HPPH").unwrap(), &settings);
+        assert_eq!(square.energy, -1);
+        // a standard benchmark, the best fold on the square lattice has energy -9
+        let hp = parse_hp("HPHPPHHPHPPHPHHPPHPH").unwrap();
+        let folded = fold(&hp, &settings);
+        assert!(is_walk(&folded.positions));
+        assert_eq!(folded.positions.len(), hp.len());
+        assert_eq!(folded.energy, energy(&hp, &folded.positions));
+        assert!(folded.energy <= -7, "{}", folded.energy);
+        assert_eq!(fold(&hp, &settings), folded);
+        assert_eq!(fold(&parse_hp("H").unwrap(), &settings).positions.len(), 1);
+    }
+}
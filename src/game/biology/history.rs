@@ -0,0 +1,464 @@
+//! Undo and redo for mRNA editing. Every change to an mRNA and its typed bases is an [`Edit`]
+//! that can be applied and reverted, each key press records one [`Step`] of them.
+//! Ctrl+Z undoes, Ctrl+Y or Ctrl+Shift+Z redoes, Backspace deletes and M mutates.
+//! A mutation or redo that finishes a product releases it like a typed stop codon.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{
+    codon_table::ActiveCodonTable,
+    editor::EditorCursor,
+    mutation::{Mutation, MutationError},
+    release_new_products, AllowedBases, Codon, CodonState, LogTextRes, Mrna, NucleobaseRna,
+    SelectedMrna,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HistorySettings>();
+    app.init_resource::<HistorySettings>();
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct HistorySettings {
+    /// Steps kept per mRNA, the oldest are forgotten first.
+    pub limit: usize,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self { limit: 100 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// A base typed onto the partial codon.
+    EnterBase(NucleobaseRna),
+    /// The first three typed bases joined the mRNA.
+    CommitCodon(Codon),
+    /// The last typed base was deleted.
+    DeleteBase(NucleobaseRna),
+    /// The last codon of the mRNA was deleted.
+    DeleteCodon(Codon),
+    /// `mutation` turned the codons `before` into `after`,
+    /// a partial codon left by a frameshift is dropped.
+    Mutate {
+        mutation: Mutation,
+        before: Vec<Codon>,
+        after: Vec<Codon>,
+    },
+    /// The editor rewrote the whole sequence, typed bases included, from `before` to `after`.
+    Replace {
+        before: Vec<NucleobaseRna>,
+        after: Vec<NucleobaseRna>,
+    },
+}
+
+impl Edit {
+    pub fn mutation(mrna: &Mrna, mutation: Mutation) -> Result<Self, MutationError> {
+        let after = Mrna::from_bases(&mutation.apply(&mrna.bases())?);
+        Ok(Edit::Mutate {
+            mutation,
+            before: mrna.codons().to_vec(),
+            after: after.codons().to_vec(),
+        })
+    }
+
+    pub fn apply(&self, mrna: &mut Mrna, codon_state: &mut CodonState) {
+        match self {
+            Edit::EnterBase(base) => codon_state.push(*base),
+            Edit::CommitCodon(_) => {
+                if let Some(codon) = codon_state.remove_head_codon() {
+                    mrna.push(codon);
+                }
+            }
+            Edit::DeleteBase(_) => {
+                codon_state.bases.pop_back();
+            }
+            Edit::DeleteCodon(_) => {
+                mrna.pop();
+            }
+            Edit::Mutate { after, .. } => mrna.set_codons(after.clone()),
+            Edit::Replace { after, .. } => set_sequence(mrna, codon_state, after),
+        }
+    }
+
+    pub fn revert(&self, mrna: &mut Mrna, codon_state: &mut CodonState) {
+        match self {
+            Edit::EnterBase(_) => {
+                codon_state.bases.pop_back();
+            }
+            Edit::CommitCodon(_) => {
+                if let Some(codon) = mrna.pop() {
+                    let (b0, b1, b2) = codon.bases;
+                    for base in [b2, b1, b0] {
+                        codon_state.bases.push_front(base);
+                    }
+                }
+            }
+            Edit::DeleteBase(base) => codon_state.push(*base),
+            Edit::DeleteCodon(codon) => mrna.push(*codon),
+            Edit::Mutate { before, .. } => mrna.set_codons(before.clone()),
+            Edit::Replace { before, .. } => set_sequence(mrna, codon_state, before),
+        }
+    }
+}
+
+/// What one key press did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub edits: Vec<Edit>,
+    /// A stop codon in this step sent the mRNA to a ribosome,
+    /// undoing it doesn't take the translation back.
+    pub translated: bool,
+}
+
+/// The undo and redo stacks of one mRNA.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct EditHistory {
+    undo: VecDeque<Step>,
+    redo: Vec<Step>,
+}
+
+impl EditHistory {
+    /// Add a step whose edits are already applied, forgetting anything that could be redone.
+    pub fn record(&mut self, step: Step, limit: usize) {
+        if step.edits.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(step);
+        while self.undo.len() > limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Apply `edits` now and record them as one step.
+    pub fn perform(
+        &mut self,
+        edits: Vec<Edit>,
+        mrna: &mut Mrna,
+        codon_state: &mut CodonState,
+        limit: usize,
+    ) {
+        for edit in &edits {
+            edit.apply(mrna, codon_state);
+        }
+        self.record(
+            Step {
+                edits,
+                translated: false,
+            },
+            limit,
+        );
+    }
+
+    pub fn undo(&mut self, mrna: &mut Mrna, codon_state: &mut CodonState) -> Option<&Step> {
+        let step = self.undo.pop_back()?;
+        for edit in step.edits.iter().rev() {
+            edit.revert(mrna, codon_state);
+        }
+        self.redo.push(step);
+        self.redo.last()
+    }
+
+    /// Redoing a translated step doesn't translate it again.
+    pub fn redo(&mut self, mrna: &mut Mrna, codon_state: &mut CodonState) -> Option<&Step> {
+        let step = self.redo.pop()?;
+        for edit in &step.edits {
+            edit.apply(mrna, codon_state);
+        }
+        self.undo.push_back(step);
+        self.undo.back()
+    }
+
+    /// The latest step sent the mRNA to a ribosome after it was performed.
+    pub fn mark_translated(&mut self) {
+        if let Some(step) = self.undo.back_mut() {
+            step.translated = true;
+        }
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+    /// Steps back to the latest one that was translated, `None` if none was.
+    pub fn since_translation(&self) -> Option<usize> {
+        self.undo.iter().rev().position(|step| step.translated)
+    }
+}
+
+impl CodonState {
+    /// The codon [`CodonState::remove_head_codon`] would take.
+    pub fn head_codon(&self) -> Option<Codon> {
+        match (self.bases.front(), self.bases.get(1), self.bases.get(2)) {
+            (Some(b0), Some(b1), Some(b2)) => Some(Codon::from_bases((*b0, *b1, *b2))),
+            _ => None,
+        }
+    }
+}
+
+impl Mrna {
+    fn pop(&mut self) -> Option<Codon> {
+        self.codons.pop()
+    }
+    fn set_codons(&mut self, codons: Vec<Codon>) {
+        self.codons = codons;
+    }
+}
+
+/// Whole codons go to the mRNA, the bases left over are typed bases.
+fn set_sequence(mrna: &mut Mrna, codon_state: &mut CodonState, bases: &[NucleobaseRna]) {
+    let whole = bases.len() / 3 * 3;
+    mrna.set_codons(
+        bases[..whole]
+            .chunks_exact(3)
+            .map(|c| Codon::from_bases((c[0], c[1], c[2])))
+            .collect(),
+    );
+    codon_state.bases = bases[whole..].iter().copied().collect();
+}
+
+/// The next allowed base after `base` in chart order, wrapping around,
+/// `None` if `base` is the only one allowed.
+fn next_base(base: NucleobaseRna, allowed_bases: &AllowedBases) -> Option<NucleobaseRna> {
+    let chart = [
+        NucleobaseRna::Uracil,
+        NucleobaseRna::Cytosine,
+        NucleobaseRna::Adenine,
+        NucleobaseRna::Guanine,
+    ];
+    let index = chart.iter().position(|b| *b == base).unwrap_or(0);
+    (1..4)
+        .map(|step| chart[(index + step) % 4])
+        .find(|b| allowed_bases.0.contains(b))
+}
+
+/// Undo, redo, Backspace to delete at the end, and M to swap the last base of the mRNA
+/// for the next allowed one. That steps through the codons of one box of the chart,
+/// which may be synonymous, change the amino acid or make a stop.
+pub fn edit_history(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<HistorySettings>,
+    allowed_bases: Res<AllowedBases>,
+    active_table: Res<ActiveCodonTable>,
+    mut mrna_q: Query<
+        (
+            Entity,
+            &mut Mrna,
+            &mut CodonState,
+            &mut EditHistory,
+            &EditorCursor,
+        ),
+        With<SelectedMrna>,
+    >,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    let Ok((entity, mut mrna, mut codon_state, mut history, cursor)) = mrna_q.get_single_mut()
+    else {
+        return;
+    };
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if ctrl && keyboard_input.just_pressed(KeyCode::KeyZ) && !shift {
+        if let Some(step) = history.undo(&mut mrna, &mut codon_state) {
+            if step.translated {
+                log_text.text = format!(
+                    "{}\nUndid a stop codon, its protein was already made",
+                    log_text.text
+                );
+            }
+        }
+    } else if ctrl
+        && (keyboard_input.just_pressed(KeyCode::KeyY)
+            || (shift && keyboard_input.just_pressed(KeyCode::KeyZ)))
+    {
+        let unedited = mrna.clone();
+        let translated = history
+            .redo(&mut mrna, &mut codon_state)
+            .map(|step| step.translated);
+        if translated == Some(false)
+            && release_new_products(&mut commands, entity, &unedited, &mrna, &active_table.table)
+        {
+            history.mark_translated();
+        }
+    } else if keyboard_input.just_pressed(KeyCode::Backspace) && cursor.is_appending() {
+        let edit = match (codon_state.bases.back(), mrna.codons().last()) {
+            (Some(base), _) => Edit::DeleteBase(*base),
+            (None, Some(codon)) => Edit::DeleteCodon(*codon),
+            (None, None) => return,
+        };
+        history.perform(vec![edit], &mut mrna, &mut codon_state, settings.limit);
+    } else if keyboard_input.just_pressed(KeyCode::KeyM) {
+        let Some(base) = mrna
+            .codons()
+            .last()
+            .and_then(|codon| next_base(codon.bases.2, &allowed_bases))
+        else {
+            return;
+        };
+        let mutation = Mutation::Substitution {
+            position: mrna.codons().len() * 3 - 1,
+            base,
+        };
+        let unedited = mrna.clone();
+        match Edit::mutation(&mrna, mutation) {
+            Ok(edit) => history.perform(vec![edit], &mut mrna, &mut codon_state, settings.limit),
+            Err(e) => warn!("{e}"),
+        }
+        if release_new_products(&mut commands, entity, &unedited, &mrna, &active_table.table) {
+            history.mark_translated();
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn typed(s: &str) -> CodonState {
+        let mut codon_state = CodonState::default();
+        for c in s.chars() {
+            codon_state.push(NucleobaseRna::try_from(c).unwrap());
+        }
+        codon_state
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut mrna = Mrna::new("AUG");
+        let mut codon_state = typed("AC");
+        let mut history = EditHistory::default();
+        // the third base and its codon are one step
+        history.perform(
+            vec![
+                Edit::EnterBase(NucleobaseRna::Uracil),
+                Edit::CommitCodon(Codon::new('A', 'C', 'U')),
+            ],
+            &mut mrna,
+            &mut codon_state,
+            10,
+        );
+        history.perform(
+            vec![Edit::EnterBase(NucleobaseRna::Cytosine)],
+            &mut mrna,
+            &mut codon_state,
+            10,
+        );
+        assert_eq!(mrna, Mrna::new("AUGACU"));
+        assert_eq!(codon_state, typed("C"));
+
+        history.undo(&mut mrna, &mut codon_state);
+        history.undo(&mut mrna, &mut codon_state);
+        assert_eq!(mrna, Mrna::new("AUG"));
+        assert_eq!(codon_state, typed("AC"));
+        assert!(history.undo(&mut mrna, &mut codon_state).is_none());
+
+        history.redo(&mut mrna, &mut codon_state);
+        assert_eq!(mrna, Mrna::new("AUGACU"));
+        assert_eq!(history.redo_len(), 1);
+        // a new edit forgets what could be redone
+        history.perform(
+            vec![Edit::DeleteCodon(Codon::new('A', 'C', 'U'))],
+            &mut mrna,
+            &mut codon_state,
+            10,
+        );
+        assert_eq!(mrna, Mrna::new("AUG"));
+        assert_eq!(history.redo_len(), 0);
+        history.undo(&mut mrna, &mut codon_state);
+        assert_eq!(mrna, Mrna::new("AUGACU"));
+    }
+
+    #[test]
+    fn test_mutation() {
+        let mut mrna = Mrna::new("AUGACU");
+        let mut codon_state = CodonState::default();
+        let mut history = EditHistory::default();
+        let edit = Edit::mutation(
+            &mrna,
+            Mutation::Substitution {
+                position: 5,
+                base: NucleobaseRna::Cytosine,
+            },
+        )
+        .unwrap();
+        history.perform(vec![edit], &mut mrna, &mut codon_state, 10);
+        assert_eq!(mrna, Mrna::new("AUGACC"));
+        history.undo(&mut mrna, &mut codon_state);
+        assert_eq!(mrna, Mrna::new("AUGACU"));
+        assert!(Edit::mutation(
+            &mrna,
+            Mutation::Deletion {
+                position: 5,
+                length: 3
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_next_base() {
+        let all = AllowedBases::default();
+        assert_eq!(
+            next_base(NucleobaseRna::Uracil, &all),
+            Some(NucleobaseRna::Cytosine)
+        );
+        assert_eq!(
+            next_base(NucleobaseRna::Guanine, &all),
+            Some(NucleobaseRna::Uracil)
+        );
+        // a level without cytosine skips it
+        let no_c = AllowedBases(vec![
+            NucleobaseRna::Uracil,
+            NucleobaseRna::Adenine,
+            NucleobaseRna::Guanine,
+        ]);
+        assert_eq!(
+            next_base(NucleobaseRna::Uracil, &no_c),
+            Some(NucleobaseRna::Adenine)
+        );
+        let only_a = AllowedBases(vec![NucleobaseRna::Adenine]);
+        assert_eq!(next_base(NucleobaseRna::Adenine, &only_a), None);
+    }
+
+    #[test]
+    fn test_limit_and_marks() {
+        let mut mrna = Mrna::default();
+        let mut codon_state = CodonState::default();
+        let mut history = EditHistory::default();
+        for (i, base) in "AUGUAAC".chars().enumerate() {
+            let base = NucleobaseRna::try_from(base).unwrap();
+            let mut edits = vec![Edit::EnterBase(base)];
+            edits[0].apply(&mut mrna, &mut codon_state);
+            if let Some(codon) = codon_state.head_codon() {
+                edits.push(Edit::CommitCodon(codon));
+                edits[1].apply(&mut mrna, &mut codon_state);
+            }
+            history.record(
+                Step {
+                    edits,
+                    translated: i == 5,
+                },
+                4,
+            );
+        }
+        assert_eq!(history.undo_len(), 4);
+        assert_eq!(history.since_translation(), Some(1));
+        for _ in 0..4 {
+            history.undo(&mut mrna, &mut codon_state);
+        }
+        // the first three steps were forgotten
+        assert_eq!(mrna, Mrna::new("AUG"));
+        assert!(codon_state.head_codon().is_none());
+        assert_eq!(history.since_translation(), None);
+    }
+}
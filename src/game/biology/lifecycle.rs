@@ -0,0 +1,196 @@
+//! Every mRNA leaves the nucleus with a 5' cap and a poly-A tail.
+//! The tail is eaten away over time, and once it is gone the message is decapped and degraded,
+//! so the player has to get it translated before then.
+
+use bevy::prelude::*;
+
+use super::{LogTextRes, Mrna, MrnaBundle, SelectedMrna, StartMrna};
+use crate::{screen::Screen, AppSet};
+
+/// Adenines in a fresh tail, about what a mammalian mRNA starts with.
+pub const POLY_A_LENGTH: u32 = 200;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DeadenylationSettings>();
+    app.init_resource::<DeadenylationSettings>();
+    app.init_resource::<Deadenylation>();
+    app.add_systems(
+        Update,
+        tick_deadenylation
+            .in_set(AppSet::TickTimers)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(
+        Update,
+        deadenylate_mrna
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct DeadenylationSettings {
+    pub adenines_per_second: f32,
+}
+
+impl Default for DeadenylationSettings {
+    fn default() -> Self {
+        Self {
+            adenines_per_second: 4.0,
+        }
+    }
+}
+
+/// Shared clock for every tail, ribosomes shorten their copy by the same amount.
+#[derive(Resource, Debug)]
+pub struct Deadenylation {
+    timer: Timer,
+}
+
+impl Default for Deadenylation {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(
+                1.0 / DeadenylationSettings::default().adenines_per_second,
+                TimerMode::Repeating,
+            ),
+        }
+    }
+}
+
+impl Deadenylation {
+    /// Adenines removed from each tail this frame.
+    pub fn removed(&self) -> u32 {
+        self.timer.times_finished_this_tick()
+    }
+}
+
+impl Mrna {
+    pub fn is_capped(&self) -> bool {
+        self.capped
+    }
+    pub fn poly_a_length(&self) -> u32 {
+        self.poly_a_length
+    }
+    pub fn with_cap(mut self, capped: bool) -> Self {
+        self.capped = capped;
+        self
+    }
+    pub fn with_poly_a_length(mut self, length: u32) -> Self {
+        self.poly_a_length = length;
+        self
+    }
+    /// A message without a tail is decapped and only waiting to be broken down.
+    pub fn is_degraded(&self) -> bool {
+        self.poly_a_length == 0
+    }
+    /// Remove `adenines` from the tail, decapping the message once it is gone.
+    /// Returns whether the message is now degraded.
+    pub fn deadenylate(&mut self, adenines: u32) -> bool {
+        self.poly_a_length = self.poly_a_length.saturating_sub(adenines);
+        if self.is_degraded() {
+            self.capped = false;
+        }
+        self.is_degraded()
+    }
+}
+
+fn tick_deadenylation(
+    time: Res<Time>,
+    settings: Res<DeadenylationSettings>,
+    mut deadenylation: ResMut<Deadenylation>,
+) {
+    if settings.is_changed() {
+        let period = 1.0 / settings.adenines_per_second.max(0.01);
+        deadenylation
+            .timer
+            .set_duration(std::time::Duration::from_secs_f32(period));
+    }
+    deadenylation.timer.tick(time.delta());
+}
+
+/// A message only starts to decay once it has a codon.
+/// A degraded message is despawned and its cell starts a fresh one in its place,
+/// from the same [`StartMrna`] so a level's given start comes back.
+fn deadenylate_mrna(
+    mut commands: Commands,
+    deadenylation: Res<Deadenylation>,
+    mut mrna_q: Query<(
+        Entity,
+        &mut Mrna,
+        Option<&StartMrna>,
+        Option<&Parent>,
+        Has<SelectedMrna>,
+    )>,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    let removed = deadenylation.removed();
+    if removed == 0 {
+        return;
+    }
+    for (entity, mut mrna, start, parent, selected) in &mut mrna_q {
+        if mrna.codons().is_empty() || !mrna.deadenylate(removed) {
+            continue;
+        }
+        info!("mRNA degraded {:?}", mrna);
+        log_text.text = format!(
+            "{}\nThe poly-A tail ran out and the mRNA was degraded, start a new one",
+            log_text.text
+        );
+        commands.entity(entity).despawn_recursive();
+        let start = start.map(|start| start.0.clone()).unwrap_or_default();
+        let mut fresh = commands.spawn(MrnaBundle::with_mrna(start));
+        if selected {
+            fresh.insert(SelectedMrna);
+        }
+        if let Some(parent) = parent {
+            fresh.set_parent(parent.get());
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_deadenylate() {
+        let mut mrna = Mrna::new("AUGUAA").with_poly_a_length(5);
+        assert!(mrna.is_capped());
+        assert!(!mrna.deadenylate(3));
+        assert_eq!(mrna.poly_a_length(), 2);
+        assert!(mrna.is_capped());
+        assert!(mrna.deadenylate(3));
+        assert_eq!(mrna.poly_a_length(), 0);
+        assert!(!mrna.is_capped());
+        assert_eq!(Mrna::default().poly_a_length(), POLY_A_LENGTH);
+    }
+    #[test]
+    fn test_degraded_mrna_restarts() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<Deadenylation>();
+        app.init_resource::<LogTextRes>();
+        app.add_systems(Update, deadenylate_mrna);
+        let start = Mrna::new("AUGACU");
+        let cell = app.world_mut().spawn_empty().id();
+        app.world_mut()
+            .spawn((
+                MrnaBundle::with_mrna(start.clone().with_poly_a_length(1)),
+                SelectedMrna,
+            ))
+            .set_parent(cell);
+        app.world_mut()
+            .resource_mut::<Deadenylation>()
+            .timer
+            .tick(std::time::Duration::from_secs(1));
+        app.update();
+        let mut mrna_q = app
+            .world_mut()
+            .query_filtered::<(&Mrna, &Parent), With<SelectedMrna>>();
+        let (mrna, parent) = mrna_q.single(app.world());
+        assert_eq!(mrna.codons(), start.codons());
+        assert!(!mrna.is_degraded());
+        assert_eq!(parent.get(), cell);
+    }
+}
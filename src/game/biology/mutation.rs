@@ -0,0 +1,341 @@
+//! Point substitutions, insertions, deletions and inversions on an mRNA,
+//! and what they do to the translated message.
+
+use thiserror::Error;
+
+use super::{translation::NucleobaseRnaToAminoChar, Codon, Mrna, NucleobaseRna};
+
+/// Positions count bases from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    Substitution {
+        position: usize,
+        base: NucleobaseRna,
+    },
+    /// Insert `bases` before `position`, `position` may be the length to append.
+    Insertion {
+        position: usize,
+        bases: Vec<NucleobaseRna>,
+    },
+    Deletion {
+        position: usize,
+        length: usize,
+    },
+    /// Replace a stretch with its reverse complement, as when a DNA segment flips over.
+    Inversion {
+        position: usize,
+        length: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationEffect {
+    /// Same translation.
+    Silent,
+    /// A different amino acid (or character) somewhere.
+    Missense,
+    /// A stop codon appears early.
+    Nonsense,
+    /// The start codon is gone.
+    StartLoss,
+    /// The stop codon is gone, translation runs on.
+    StopLoss,
+    /// The length changed by something other than a multiple of three.
+    Frameshift,
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum MutationError {
+    #[error("mutation at {position}..{end} is outside the {length} bases")]
+    OutOfRange {
+        position: usize,
+        end: usize,
+        length: usize,
+    },
+}
+
+/// The mutated sequence and how it changed the translation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationOutcome {
+    /// All mutated bases, a frameshift can leave a partial codon at the end.
+    pub bases: Vec<NucleobaseRna>,
+    /// The whole codons of `bases`.
+    pub mrna: Mrna,
+    pub effect: MutationEffect,
+}
+
+impl Mutation {
+    pub fn apply(&self, bases: &[NucleobaseRna]) -> Result<Vec<NucleobaseRna>, MutationError> {
+        let (position, span) = match self {
+            Mutation::Substitution { position, .. } => (*position, 1),
+            Mutation::Insertion { position, .. } => (*position, 0),
+            Mutation::Deletion { position, length } | Mutation::Inversion { position, length } => {
+                (*position, *length)
+            }
+        };
+        let out_of_range = MutationError::OutOfRange {
+            position,
+            end: position.saturating_add(span),
+            length: bases.len(),
+        };
+        let end = position
+            .checked_add(span)
+            .filter(|end| *end <= bases.len())
+            .ok_or(out_of_range)?;
+        let mut mutated = bases.to_vec();
+        match self {
+            Mutation::Substitution { base, .. } => mutated[position] = *base,
+            Mutation::Insertion { bases, .. } => {
+                mutated.splice(position..position, bases.iter().copied());
+            }
+            Mutation::Deletion { .. } => {
+                mutated.drain(position..end);
+            }
+            Mutation::Inversion { .. } => {
+                let inverted: Vec<NucleobaseRna> = bases[position..end]
+                    .iter()
+                    .rev()
+                    .map(NucleobaseRna::complement)
+                    .collect();
+                mutated.splice(position..end, inverted);
+            }
+        }
+        Ok(mutated)
+    }
+}
+
+/// Compare two sequences read in frame from their first base with `table`.
+/// Translation is taken to begin at the first start codon of `before` and end at the first
+/// stop after it, codons past the first difference are matched up across an in-frame indel.
+pub fn classify(
+    before: &[NucleobaseRna],
+    after: &[NucleobaseRna],
+    table: &NucleobaseRnaToAminoChar,
+) -> MutationEffect {
+    if before.len().abs_diff(after.len()) % 3 != 0 {
+        return MutationEffect::Frameshift;
+    }
+    let changed = before.iter().zip(after).take_while(|(b, a)| b == a).count() / 3;
+    let shift = (after.len() / 3) as isize - (before.len() / 3) as isize;
+    let before = codons(before);
+    let after = codons(after);
+    // where a codon of `before` ends up in `after`
+    let moved = |i: usize| {
+        if i < changed {
+            i as isize
+        } else {
+            i as isize + shift
+        }
+    };
+    let (start, after_start) = match before.iter().position(|c| table.is_start(c)) {
+        Some(start) => {
+            let after_start = moved(start);
+            let kept = usize::try_from(after_start)
+                .ok()
+                .and_then(|i| after.get(i))
+                .is_some_and(|c| table.is_start(c));
+            if !kept {
+                return MutationEffect::StartLoss;
+            }
+            (start, after_start as usize)
+        }
+        None => (0, 0),
+    };
+    let first_stop = |codons: &[Codon], start: usize| {
+        codons
+            .iter()
+            .skip(start)
+            .position(|c| table.is_stop(c))
+            .map(|i| i + start)
+    };
+    let stop = first_stop(&before, start);
+    let after_stop = first_stop(&after, after_start);
+    match (stop, after_stop) {
+        (Some(_), None) => return MutationEffect::StopLoss,
+        (None, Some(_)) => return MutationEffect::Nonsense,
+        (Some(stop), Some(after_stop)) => match (after_stop as isize).cmp(&moved(stop)) {
+            std::cmp::Ordering::Less => return MutationEffect::Nonsense,
+            std::cmp::Ordering::Greater => return MutationEffect::StopLoss,
+            std::cmp::Ordering::Equal => {}
+        },
+        (None, None) => {}
+    }
+    let translate = |codons: &[Codon], start: usize, stop: Option<usize>| -> String {
+        let end = stop.map_or(codons.len(), |stop| stop + 1);
+        codons[start..end]
+            .iter()
+            .map(|c| table.translation(c))
+            .collect()
+    };
+    if translate(&before, start, stop) == translate(&after, after_start, after_stop) {
+        MutationEffect::Silent
+    } else {
+        MutationEffect::Missense
+    }
+}
+
+fn codons(bases: &[NucleobaseRna]) -> Vec<Codon> {
+    bases
+        .chunks_exact(3)
+        .map(|b| Codon::from_bases((b[0], b[1], b[2])))
+        .collect()
+}
+
+impl Mrna {
+    /// Apply `mutation` and classify what it does to the translation with `table`.
+    pub fn mutate(
+        &self,
+        mutation: &Mutation,
+        table: &NucleobaseRnaToAminoChar,
+    ) -> Result<MutationOutcome, MutationError> {
+        let before = self.bases();
+        let bases = mutation.apply(&before)?;
+        Ok(MutationOutcome {
+            effect: classify(&before, &bases, table),
+            mrna: Mrna::from_bases(&bases),
+            bases,
+        })
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::{translation::TranslationMode, CODON_TABLE};
+
+    #[allow(dead_code)]
+    fn toki() -> Mrna {
+        Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA")
+    }
+
+    #[test]
+    fn test_effects() {
+        let substitute = |position, base| Mutation::Substitution { position, base };
+        let effect = |m: Mutation| toki().mutate(&m, &CODON_TABLE).unwrap().effect;
+        // CGA k -> CGG k
+        assert_eq!(
+            effect(substitute(11, NucleobaseRna::Guanine)),
+            MutationEffect::Silent
+        );
+        // ACU T -> ACC t
+        assert_eq!(
+            effect(substitute(5, NucleobaseRna::Cytosine)),
+            MutationEffect::Missense
+        );
+        // CAG O -> UAG stop
+        assert_eq!(
+            effect(substitute(6, NucleobaseRna::Uracil)),
+            MutationEffect::Nonsense
+        );
+        assert_eq!(
+            effect(substitute(2, NucleobaseRna::Adenine)),
+            MutationEffect::StartLoss
+        );
+        // UGA stop -> UGG
+        assert_eq!(
+            effect(substitute(32, NucleobaseRna::Guanine)),
+            MutationEffect::StopLoss
+        );
+        assert_eq!(
+            effect(Mutation::Deletion {
+                position: 3,
+                length: 1
+            }),
+            MutationEffect::Frameshift
+        );
+        assert_eq!(
+            effect(Mutation::Deletion {
+                position: 3,
+                length: 3
+            }),
+            MutationEffect::Missense
+        );
+    }
+
+    #[test]
+    fn test_insertion_garbles_message() {
+        let outcome = toki()
+            .mutate(
+                &Mutation::Insertion {
+                    position: 4,
+                    bases: vec![NucleobaseRna::Adenine],
+                },
+                &CODON_TABLE,
+            )
+            .unwrap();
+        assert_eq!(outcome.effect, MutationEffect::Frameshift);
+        assert_eq!(outcome.bases.len(), 34);
+        assert_ne!(outcome.mrna.to_string_toki(), "^Toki Pona.");
+    }
+
+    #[test]
+    fn test_inversion() {
+        let mrna = Mrna::new("AUGCCCUAA");
+        let outcome = mrna
+            .mutate(
+                &Mutation::Inversion {
+                    position: 3,
+                    length: 3,
+                },
+                &CODON_TABLE,
+            )
+            .unwrap();
+        assert_eq!(outcome.mrna, Mrna::new("AUGGGGUAA"));
+    }
+
+    #[test]
+    fn test_active_table() {
+        // UGG and UGA are both W in vertebrate mitochondria, in the standard code it'd be nonsense
+        let mito = NucleobaseRnaToAminoChar::new(TranslationMode::VertebrateMitochondrial);
+        let outcome = Mrna::new("AUGUGGUAA")
+            .mutate(
+                &Mutation::Substitution {
+                    position: 5,
+                    base: NucleobaseRna::Adenine,
+                },
+                &mito,
+            )
+            .unwrap();
+        assert_eq!(outcome.effect, MutationEffect::Silent);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(
+            toki().mutate(
+                &Mutation::Deletion {
+                    position: 30,
+                    length: 9
+                },
+                &CODON_TABLE
+            ),
+            Err(MutationError::OutOfRange {
+                position: 30,
+                end: 39,
+                length: 33
+            })
+        );
+        // would overflow past the range check
+        assert!(matches!(
+            toki().mutate(
+                &Mutation::Deletion {
+                    position: usize::MAX,
+                    length: 1
+                },
+                &CODON_TABLE
+            ),
+            Err(MutationError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            toki().mutate(
+                &Mutation::Substitution {
+                    position: usize::MAX,
+                    base: NucleobaseRna::Adenine
+                },
+                &CODON_TABLE
+            ),
+            Err(MutationError::OutOfRange { .. })
+        ));
+    }
+}
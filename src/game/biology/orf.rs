@@ -0,0 +1,152 @@
+use super::{
+    dna::Dna,
+    parse::{parse_rna_bases, SequenceError},
+    translation::NucleobaseRnaToAminoChar,
+    Codon, Mrna, NucleobaseRna,
+};
+
+/// Which strand of a double stranded sequence an ORF was found on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+/// An AUG…stop stretch found by [`find_orfs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenReadingFrame {
+    pub strand: Strand,
+    /// 0, 1 or 2, the offset of the first codon of the frame.
+    pub frame: usize,
+    /// Base offset of the start codon, counted 5' to 3' along `strand`.
+    pub start: usize,
+    /// Number of bases including the start and stop codons.
+    pub length: usize,
+    pub translation: String,
+}
+
+impl OpenReadingFrame {
+    /// Base offset one past the stop codon.
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+    pub fn codon_count(&self) -> usize {
+        self.length / 3
+    }
+}
+
+/// Scan the three forward frames of `bases` for complete ORFs.
+/// An ORF opens on the first start codon of `table` in a frame and closes on the next stop,
+/// start codons inside an open ORF are translated as normal codons.
+pub fn find_orfs(
+    bases: &[NucleobaseRna],
+    table: &NucleobaseRnaToAminoChar,
+) -> Vec<OpenReadingFrame> {
+    scan_strand(bases, table, Strand::Forward)
+}
+
+fn scan_strand(
+    bases: &[NucleobaseRna],
+    table: &NucleobaseRnaToAminoChar,
+    strand: Strand,
+) -> Vec<OpenReadingFrame> {
+    let mut orfs = Vec::new();
+    for frame in 0..3 {
+        let mut open: Option<(usize, String)> = None;
+        let mut i = frame;
+        while i + 3 <= bases.len() {
+            let codon = Codon::from_bases((bases[i], bases[i + 1], bases[i + 2]));
+            let c = table.translation(&codon);
+            match open.as_mut() {
+                None if table.is_start(&codon) => open = Some((i, c.to_string())),
+                None => {}
+                Some((start, translation)) => {
+                    translation.push(c);
+                    if table.is_stop(&codon) {
+                        orfs.push(OpenReadingFrame {
+                            strand,
+                            frame,
+                            start: *start,
+                            length: i + 3 - *start,
+                            translation: translation.clone(),
+                        });
+                        open = None;
+                    }
+                }
+            }
+            i += 3;
+        }
+    }
+    orfs.sort_by_key(|orf| orf.start);
+    orfs
+}
+
+/// [`find_orfs`] on a raw string of RNA bases like `"AUGACU…"`.
+pub fn find_orfs_str(
+    s: &str,
+    table: &NucleobaseRnaToAminoChar,
+) -> Result<Vec<OpenReadingFrame>, SequenceError> {
+    Ok(find_orfs(&parse_rna_bases(s)?, table))
+}
+
+impl Mrna {
+    /// ORFs in all three forward frames.
+    pub fn open_reading_frames(&self, table: &NucleobaseRnaToAminoChar) -> Vec<OpenReadingFrame> {
+        find_orfs(&self.bases(), table)
+    }
+}
+
+impl Dna {
+    /// ORFs in all six frames, three on each strand.
+    /// Offsets on the reverse strand count from the 5' end of the reverse complement.
+    pub fn open_reading_frames(&self, table: &NucleobaseRnaToAminoChar) -> Vec<OpenReadingFrame> {
+        let mut orfs = scan_strand(&self.transcribe_bases(), table, Strand::Forward);
+        orfs.extend(scan_strand(
+            &self.reverse_complement().transcribe_bases(),
+            table,
+            Strand::Reverse,
+        ));
+        orfs
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::CODON_TABLE;
+    #[test]
+    fn test_orfs_in_frame() {
+        let orfs = Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA").open_reading_frames(&CODON_TABLE);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].frame, 0);
+        assert_eq!(orfs[0].start, 0);
+        assert_eq!(orfs[0].length, 33);
+        assert_eq!(orfs[0].translation, "^Toki Pona.");
+    }
+    #[test]
+    fn test_orfs_frame_shifted() {
+        // one extra base hides the message in frame 1
+        let orfs = find_orfs_str("CAUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA", &CODON_TABLE).unwrap();
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].frame, 1);
+        assert_eq!(orfs[0].start, 1);
+        assert_eq!(orfs[0].end(), 34);
+        assert_eq!(orfs[0].translation, "^Toki Pona.");
+    }
+    #[test]
+    fn test_orfs_without_stop_are_ignored() {
+        assert!(find_orfs_str("AUGACUCAG", &CODON_TABLE).unwrap().is_empty());
+        assert!(find_orfs_str("AUGXCUCAG", &CODON_TABLE).is_err());
+    }
+    #[test]
+    fn test_orfs_six_frames() {
+        // reverse complement of ATGAAATAG
+        let dna = Dna::new("CTATTTCAT");
+        let orfs = dna.open_reading_frames(&CODON_TABLE);
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(orfs[0].strand, Strand::Reverse);
+        assert_eq!(orfs[0].frame, 0);
+        assert_eq!(orfs[0].translation, "^E.");
+    }
+}
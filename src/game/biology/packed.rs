@@ -0,0 +1,300 @@
+//! Two bits per base, for sequences far longer than anything typed by hand,
+//! like a few million bases of bacterial genome in the sandbox.
+//! A FASTA record packs with [`super::fasta::FastaRecord::packed`].
+//! Base `i` sits in word `i / 32` at bit `2 * (i % 32)`, coded by [`NucleobaseRna::to_index`].
+
+use std::{ops::Range, str::FromStr};
+
+use super::{base_from_last_index, parse::SequenceError, Codon, Mrna, NucleobaseRna};
+
+const BASES_PER_WORD: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PackedBases {
+    /// Bits past `len` in the last word are always zero, so equal sequences compare equal.
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl PackedBases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_capacity(bases: usize) -> Self {
+        PackedBases {
+            words: Vec::with_capacity(bases.div_ceil(BASES_PER_WORD)),
+            len: 0,
+        }
+    }
+    pub fn from_bases(bases: &[NucleobaseRna]) -> Self {
+        let mut packed = PackedBases::with_capacity(bases.len());
+        packed.extend(bases.iter().copied());
+        packed
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Heap bytes used by the bases, a quarter of the base count rounded up to whole words.
+    pub fn byte_len(&self) -> usize {
+        self.words.len() * std::mem::size_of::<u64>()
+    }
+
+    pub fn get(&self, i: usize) -> Option<NucleobaseRna> {
+        if i >= self.len {
+            return None;
+        }
+        let word = self.words[i / BASES_PER_WORD];
+        Some(base_from_last_index((word >> shift(i)) as usize))
+    }
+    pub fn push(&mut self, base: NucleobaseRna) {
+        if self.len % BASES_PER_WORD == 0 {
+            self.words.push(0);
+        }
+        let last = self.words.len() - 1;
+        self.words[last] |= (base.to_index() as u64) << shift(self.len);
+        self.len += 1;
+    }
+    pub fn pop(&mut self) -> Option<NucleobaseRna> {
+        let base = self.get(self.len.checked_sub(1)?)?;
+        self.len -= 1;
+        if self.len % BASES_PER_WORD == 0 {
+            self.words.pop();
+        } else {
+            let last = self.words.len() - 1;
+            self.words[last] &= !(0b11 << shift(self.len));
+        }
+        Some(base)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            words: &self.words,
+            index: 0,
+            len: self.len,
+            word: 0,
+        }
+    }
+
+    /// A copy of `range`, whole words at a time. Panics if the range is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> PackedBases {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "slice {range:?} of {} bases",
+            self.len
+        );
+        let len = range.len();
+        let first = range.start / BASES_PER_WORD;
+        let offset = shift(range.start);
+        let mut words: Vec<u64> = (first..first + len.div_ceil(BASES_PER_WORD))
+            .map(|w| {
+                let low = self.words[w] >> offset;
+                let high = match (offset, self.words.get(w + 1)) {
+                    (0, _) | (_, None) => 0,
+                    (_, Some(next)) => next << (64 - offset),
+                };
+                low | high
+            })
+            .collect();
+        if len % BASES_PER_WORD != 0 {
+            if let Some(last) = words.last_mut() {
+                *last &= (1 << shift(len)) - 1;
+            }
+        }
+        PackedBases { words, len }
+    }
+
+    /// Whole codons of the reading frame starting at base `frame`.
+    pub fn codons(&self, frame: usize) -> impl Iterator<Item = Codon> + '_ {
+        let mut bases = self.iter().skip(frame);
+        std::iter::from_fn(move || {
+            Some(Codon::from_bases((
+                bases.next()?,
+                bases.next()?,
+                bases.next()?,
+            )))
+        })
+    }
+
+    pub fn to_bases(&self) -> Vec<NucleobaseRna> {
+        self.iter().collect()
+    }
+    /// The frame 0 codons as an mRNA, a trailing partial codon is dropped.
+    pub fn to_mrna(&self) -> Mrna {
+        let mut mrna = Mrna::default();
+        for codon in self.codons(0) {
+            mrna.push(codon);
+        }
+        mrna
+    }
+}
+
+/// Bit offset of base `i` inside its word.
+fn shift(i: usize) -> usize {
+    2 * (i % BASES_PER_WORD)
+}
+
+pub struct Iter<'a> {
+    words: &'a [u64],
+    index: usize,
+    len: usize,
+    /// What is left of the current word, the next base in the low bits.
+    word: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = NucleobaseRna;
+    fn next(&mut self) -> Option<NucleobaseRna> {
+        if self.index >= self.len {
+            return None;
+        }
+        if self.index % BASES_PER_WORD == 0 {
+            self.word = self.words[self.index / BASES_PER_WORD];
+        }
+        let base = base_from_last_index(self.word as usize);
+        self.word >>= 2;
+        self.index += 1;
+        Some(base)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.len - self.index;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl<'a> IntoIterator for &'a PackedBases {
+    type Item = NucleobaseRna;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl Extend<NucleobaseRna> for PackedBases {
+    fn extend<I: IntoIterator<Item = NucleobaseRna>>(&mut self, iter: I) {
+        for base in iter {
+            self.push(base);
+        }
+    }
+}
+
+impl FromIterator<NucleobaseRna> for PackedBases {
+    fn from_iter<I: IntoIterator<Item = NucleobaseRna>>(iter: I) -> Self {
+        let mut packed = PackedBases::new();
+        packed.extend(iter);
+        packed
+    }
+}
+
+impl From<&Mrna> for PackedBases {
+    fn from(mrna: &Mrna) -> Self {
+        PackedBases::from_bases(&mrna.bases())
+    }
+}
+
+impl FromStr for PackedBases {
+    type Err = SequenceError;
+    /// Upper or lower case, DNA `T` is read as `U` and whitespace is skipped,
+    /// so a genome can be pasted with its line breaks. Packs as it goes, with no
+    /// byte-per-base copy on the way.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut packed = PackedBases::with_capacity(s.len());
+        for (position, c) in s.chars().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let base = match c.to_ascii_uppercase() {
+                'T' => Some(NucleobaseRna::Uracil),
+                upper => NucleobaseRna::from_char(upper),
+            };
+            packed.push(base.ok_or(SequenceError::InvalidBase { position, c })?);
+        }
+        Ok(packed)
+    }
+}
+
+impl std::fmt::Display for PackedBases {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for base in self {
+            write!(f, "{}", base.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::parse::parse_rna_bases;
+
+    #[allow(dead_code)]
+    fn long_bases() -> Vec<NucleobaseRna> {
+        // 100 bases, three words and a bit
+        let bases = parse_rna_bases("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA").unwrap();
+        bases.iter().cycle().take(100).copied().collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bases = long_bases();
+        let packed = PackedBases::from_bases(&bases);
+        assert_eq!(packed.len(), 100);
+        assert_eq!(packed.byte_len(), 4 * 8);
+        assert_eq!(packed.to_bases(), bases);
+        assert_eq!(packed.iter().len(), 100);
+        assert_eq!(packed.get(99), Some(bases[99]));
+        assert_eq!(packed.get(100), None);
+        let mrna = Mrna::new("AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA");
+        assert_eq!(PackedBases::from(&mrna).to_mrna(), mrna);
+        assert_eq!(PackedBases::from(&mrna).to_string(), mrna.to_string_bases());
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let bases = long_bases();
+        let mut packed = PackedBases::from_bases(&bases);
+        for expected in bases[60..].iter().rev() {
+            assert_eq!(packed.pop(), Some(*expected));
+        }
+        // popping clears the bits, so it equals a fresh copy of the rest
+        assert_eq!(packed, PackedBases::from_bases(&bases[..60]));
+        assert_eq!(packed.byte_len(), 2 * 8);
+        let mut empty = PackedBases::new();
+        assert_eq!(empty.pop(), None);
+    }
+
+    #[test]
+    fn test_slice() {
+        let bases = long_bases();
+        let packed = PackedBases::from_bases(&bases);
+        for range in [0..100, 0..32, 32..64, 5..7, 31..97, 50..50, 99..100] {
+            assert_eq!(
+                packed.slice(range.clone()),
+                PackedBases::from_bases(&bases[range.clone()]),
+                "{range:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_codons_and_parse() {
+        let packed: PackedBases = "atg act\ncag t".parse().unwrap();
+        assert_eq!(packed.to_string(), "AUGACUCAGU");
+        let frame_1: Vec<String> = packed.codons(1).map(|c| c.to_string()).collect();
+        assert_eq!(frame_1, ["UGA", "CUC", "AGU"]);
+        assert_eq!(packed.codons(0).count(), 3);
+        assert_eq!(
+            "AUX".parse::<PackedBases>(),
+            Err(SequenceError::InvalidBase {
+                position: 2,
+                c: 'X'
+            })
+        );
+    }
+}
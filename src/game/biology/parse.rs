@@ -0,0 +1,168 @@
+//! Fallible parsing of bases, codons and sequences, for text typed or pasted by players
+//! and loaded from files. Use the `new` constructors only for literals known to be valid.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use super::{
+    dna::{Dna, NucleobaseDna},
+    Codon, Mrna, NucleobaseRna,
+};
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    /// `position` counts characters from 0.
+    #[error("'{c}' at position {position} is not a nucleobase")]
+    InvalidBase { position: usize, c: char },
+    #[error("expected {expected} bases, got {length}")]
+    WrongLength { expected: usize, length: usize },
+    #[error("{length} bases don't split into codons, {} left over", length % 3)]
+    IncompleteCodon { length: usize },
+}
+
+fn parse_bases<B>(s: &str, from_char: fn(char) -> Option<B>) -> Result<Vec<B>, SequenceError> {
+    s.chars()
+        .enumerate()
+        .map(|(position, c)| from_char(c).ok_or(SequenceError::InvalidBase { position, c }))
+        .collect()
+}
+
+/// Parse loose RNA bases, without requiring whole codons.
+pub fn parse_rna_bases(s: &str) -> Result<Vec<NucleobaseRna>, SequenceError> {
+    parse_bases(s, NucleobaseRna::from_char)
+}
+
+impl TryFrom<char> for NucleobaseRna {
+    type Error = SequenceError;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        NucleobaseRna::from_char(c).ok_or(SequenceError::InvalidBase { position: 0, c })
+    }
+}
+
+impl FromStr for NucleobaseRna {
+    type Err = SequenceError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_rna_bases(s)?.as_slice() {
+            [base] => Ok(*base),
+            bases => Err(SequenceError::WrongLength {
+                expected: 1,
+                length: bases.len(),
+            }),
+        }
+    }
+}
+
+impl FromStr for Codon {
+    type Err = SequenceError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_rna_bases(s)?.as_slice() {
+            [b1, b2, b3] => Ok(Codon::from_bases((*b1, *b2, *b3))),
+            bases => Err(SequenceError::WrongLength {
+                expected: 3,
+                length: bases.len(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<&str> for Codon {
+    type Error = SequenceError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl FromStr for Mrna {
+    type Err = SequenceError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bases = parse_rna_bases(s)?;
+        if bases.len() % 3 != 0 {
+            return Err(SequenceError::IncompleteCodon {
+                length: bases.len(),
+            });
+        }
+        Ok(Mrna::from_bases(&bases))
+    }
+}
+
+impl TryFrom<&str> for Mrna {
+    type Error = SequenceError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<char> for NucleobaseDna {
+    type Error = SequenceError;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        NucleobaseDna::from_char(c).ok_or(SequenceError::InvalidBase { position: 0, c })
+    }
+}
+
+impl FromStr for Dna {
+    type Err = SequenceError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Dna::from_bases(parse_bases(s, NucleobaseDna::from_char)?))
+    }
+}
+
+impl TryFrom<&str> for Dna {
+    type Error = SequenceError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_parse_ok() {
+        assert_eq!("G".parse(), Ok(NucleobaseRna::Guanine));
+        assert_eq!("AUG".parse(), Ok(Codon::new('A', 'U', 'G')));
+        assert_eq!(
+            "AUGACUCAGCGAAUAGUUCCUCAGAACGCGUGA"
+                .parse::<Mrna>()
+                .unwrap()
+                .to_string_toki(),
+            "^Toki Pona."
+        );
+        assert_eq!(Dna::try_from("ATG"), Ok(Dna::new("ATG")));
+    }
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "AUGAXG".parse::<Mrna>(),
+            Err(SequenceError::InvalidBase {
+                position: 4,
+                c: 'X'
+            })
+        );
+        assert_eq!(
+            "AUGA".parse::<Mrna>(),
+            Err(SequenceError::IncompleteCodon { length: 4 })
+        );
+        assert_eq!(
+            Codon::try_from("AU"),
+            Err(SequenceError::WrongLength {
+                expected: 3,
+                length: 2
+            })
+        );
+        assert_eq!(
+            NucleobaseRna::try_from('T'),
+            Err(SequenceError::InvalidBase {
+                position: 0,
+                c: 'T'
+            })
+        );
+        assert_eq!(
+            "AUG".parse::<Dna>(),
+            Err(SequenceError::InvalidBase {
+                position: 1,
+                c: 'U'
+            })
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Amino acids and the chains the real genetic code builds from them.
+
+use super::{
+    translation::{NucleobaseRnaToAminoChar, TranslationMode},
+    Mrna,
+};
+
+/// Mass of the water lost for every peptide bond.
+const WATER_DA: f32 = 18.015;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AminoAcid {
+    Alanine,
+    Arginine,
+    Asparagine,
+    AsparticAcid,
+    Cysteine,
+    Glutamine,
+    GlutamicAcid,
+    Glycine,
+    Histidine,
+    Isoleucine,
+    Leucine,
+    Lysine,
+    Methionine,
+    Phenylalanine,
+    Proline,
+    Serine,
+    Threonine,
+    Tryptophan,
+    Tyrosine,
+    Valine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SideChainClass {
+    Nonpolar,
+    Polar,
+    Acidic,
+    Basic,
+}
+
+impl AminoAcid {
+    pub const ALL: [AminoAcid; 20] = [
+        AminoAcid::Alanine,
+        AminoAcid::Arginine,
+        AminoAcid::Asparagine,
+        AminoAcid::AsparticAcid,
+        AminoAcid::Cysteine,
+        AminoAcid::Glutamine,
+        AminoAcid::GlutamicAcid,
+        AminoAcid::Glycine,
+        AminoAcid::Histidine,
+        AminoAcid::Isoleucine,
+        AminoAcid::Leucine,
+        AminoAcid::Lysine,
+        AminoAcid::Methionine,
+        AminoAcid::Phenylalanine,
+        AminoAcid::Proline,
+        AminoAcid::Serine,
+        AminoAcid::Threonine,
+        AminoAcid::Tryptophan,
+        AminoAcid::Tyrosine,
+        AminoAcid::Valine,
+    ];
+
+    /// The one letter code used by [`TranslationMode::Real`].
+    pub fn from_char(c: char) -> Option<Self> {
+        AminoAcid::ALL.into_iter().find(|a| a.to_char() == c)
+    }
+    pub fn to_char(&self) -> char {
+        self.data().0
+    }
+    pub fn three_letter_code(&self) -> &'static str {
+        self.data().1
+    }
+    pub fn name(&self) -> &'static str {
+        self.data().2
+    }
+    /// Average mass of the free amino acid in daltons.
+    pub fn molecular_weight(&self) -> f32 {
+        self.data().3
+    }
+    /// Kyte-Doolittle hydropathy index, positive is hydrophobic.
+    pub fn hydropathy(&self) -> f32 {
+        self.data().4
+    }
+    /// Net side chain charge at pH 7.
+    pub fn charge(&self) -> i8 {
+        self.data().5
+    }
+    pub fn side_chain_class(&self) -> SideChainClass {
+        self.data().6
+    }
+    /// pKa of the ionisable side chain (EMBOSS values), with `true` for bases.
+    fn side_chain_pka(&self) -> Option<(f32, bool)> {
+        match self {
+            AminoAcid::Cysteine => Some((8.5, false)),
+            AminoAcid::AsparticAcid => Some((3.9, false)),
+            AminoAcid::GlutamicAcid => Some((4.1, false)),
+            AminoAcid::Histidine => Some((6.5, true)),
+            AminoAcid::Lysine => Some((10.8, true)),
+            AminoAcid::Arginine => Some((12.5, true)),
+            AminoAcid::Tyrosine => Some((10.1, false)),
+            _ => None,
+        }
+    }
+
+    #[rustfmt::skip]
+    fn data(&self) -> (char, &'static str, &'static str, f32, f32, i8, SideChainClass) {
+        use SideChainClass::*;
+        match self {
+            AminoAcid::Alanine => ('A', "Ala", "Alanine", 89.09, 1.8, 0, Nonpolar),
+            AminoAcid::Arginine => ('R', "Arg", "Arginine", 174.20, -4.5, 1, Basic),
+            AminoAcid::Asparagine => ('N', "Asn", "Asparagine", 132.12, -3.5, 0, Polar),
+            AminoAcid::AsparticAcid => ('D', "Asp", "Aspartic acid", 133.10, -3.5, -1, Acidic),
+            AminoAcid::Cysteine => ('C', "Cys", "Cysteine", 121.16, 2.5, 0, Polar),
+            AminoAcid::Glutamine => ('Q', "Gln", "Glutamine", 146.15, -3.5, 0, Polar),
+            AminoAcid::GlutamicAcid => ('E', "Glu", "Glutamic acid", 147.13, -3.5, -1, Acidic),
+            AminoAcid::Glycine => ('G', "Gly", "Glycine", 75.07, -0.4, 0, Nonpolar),
+            AminoAcid::Histidine => ('H', "His", "Histidine", 155.16, -3.2, 0, Basic),
+            AminoAcid::Isoleucine => ('I', "Ile", "Isoleucine", 131.17, 4.5, 0, Nonpolar),
+            AminoAcid::Leucine => ('L', "Leu", "Leucine", 131.17, 3.8, 0, Nonpolar),
+            AminoAcid::Lysine => ('K', "Lys", "Lysine", 146.19, -3.9, 1, Basic),
+            AminoAcid::Methionine => ('M', "Met", "Methionine", 149.21, 1.9, 0, Nonpolar),
+            AminoAcid::Phenylalanine => ('F', "Phe", "Phenylalanine", 165.19, 2.8, 0, Nonpolar),
+            AminoAcid::Proline => ('P', "Pro", "Proline", 115.13, -1.6, 0, Nonpolar),
+            AminoAcid::Serine => ('S', "Ser", "Serine", 105.09, -0.8, 0, Polar),
+            AminoAcid::Threonine => ('T', "Thr", "Threonine", 119.12, -0.7, 0, Polar),
+            AminoAcid::Tryptophan => ('W', "Trp", "Tryptophan", 204.23, -0.9, 0, Nonpolar),
+            AminoAcid::Tyrosine => ('Y', "Tyr", "Tyrosine", 181.19, -1.3, 0, Polar),
+            AminoAcid::Valine => ('V', "Val", "Valine", 117.15, 4.2, 0, Nonpolar),
+        }
+    }
+}
+
+/// A chain of amino acids, N-terminus first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Polypeptide {
+    pub residues: Vec<AminoAcid>,
+}
+
+impl Polypeptide {
+    /// Parse one letter codes like `"MTQR"`, `None` if any letter isn't an amino acid.
+    pub fn from_one_letter(s: &str) -> Option<Self> {
+        let residues = s.chars().map(AminoAcid::from_char).collect::<Option<_>>()?;
+        Some(Polypeptide { residues })
+    }
+
+    /// Translate `mrna` with the standard code, from its first start codon up to the next stop.
+    /// `None` if there is no start codon.
+    pub fn translate(mrna: &Mrna) -> Option<Self> {
+        Polypeptide::translate_with(mrna, &NucleobaseRnaToAminoChar::new(TranslationMode::Real))
+    }
+
+    /// Like [`Polypeptide::translate`] with any code whose output is amino acid letters.
+    pub fn translate_with(mrna: &Mrna, table: &NucleobaseRnaToAminoChar) -> Option<Self> {
+        let codons = mrna.codons();
+        let start = codons.iter().position(|c| table.is_start(c))?;
+        let mut residues = Vec::new();
+        for (i, codon) in codons.iter().enumerate().skip(start) {
+            if table.is_stop(codon) {
+                break;
+            }
+            // an alternative start codon still brings in methionine
+            let c = if i == start {
+                'M'
+            } else {
+                table.translation(codon)
+            };
+            residues.extend(AminoAcid::from_char(c));
+        }
+        Some(Polypeptide { residues })
+    }
+
+    pub fn len(&self) -> usize {
+        self.residues.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.residues.is_empty()
+    }
+    pub fn to_string_one_letter(&self) -> String {
+        self.residues.iter().map(AminoAcid::to_char).collect()
+    }
+
+    /// Average mass in daltons, one water is lost per peptide bond.
+    pub fn mass(&self) -> f32 {
+        let residues: f32 = self.residues.iter().map(AminoAcid::molecular_weight).sum();
+        residues - WATER_DA * self.len().saturating_sub(1) as f32
+    }
+
+    /// Grand average of hydropathy, the mean Kyte-Doolittle index.
+    pub fn gravy(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.residues.iter().map(AminoAcid::hydropathy).sum::<f32>() / self.len() as f32
+    }
+
+    /// Net charge at `ph` from the Henderson-Hasselbalch equation,
+    /// counting both termini and every ionisable side chain.
+    pub fn charge_at(&self, ph: f32) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let positive = |pka: f32| 1.0 / (1.0 + 10f32.powf(ph - pka));
+        let negative = |pka: f32| -1.0 / (1.0 + 10f32.powf(pka - ph));
+        let termini = positive(8.6) + negative(3.6);
+        termini
+            + self
+                .residues
+                .iter()
+                .filter_map(AminoAcid::side_chain_pka)
+                .map(
+                    |(pka, basic)| {
+                        if basic {
+                            positive(pka)
+                        } else {
+                            negative(pka)
+                        }
+                    },
+                )
+                .sum::<f32>()
+    }
+
+    /// The pH at which the chain carries no net charge, found by bisection.
+    pub fn isoelectric_point(&self) -> f32 {
+        let (mut low, mut high) = (0.0, 14.0);
+        while high - low > 0.001 {
+            let mid = (low + high) / 2.0;
+            if self.charge_at(mid) > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.0
+    }
+}
+
+impl std::fmt::Display for Polypeptide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let codes: Vec<&str> = self
+            .residues
+            .iter()
+            .map(AminoAcid::three_letter_code)
+            .collect();
+        f.write_str(&codes.join("-"))
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_translate() {
+        let mrna = Mrna::new("GGGAUGACUCAGCGAAUAGUUCCUCAGAACGCGUGAGCU");
+        let chain = Polypeptide::translate(&mrna).unwrap();
+        assert_eq!(chain.to_string_one_letter(), "MTQRIVPQNA");
+        assert_eq!(chain.to_string(), "Met-Thr-Gln-Arg-Ile-Val-Pro-Gln-Asn-Ala");
+        assert_eq!(Polypeptide::translate(&Mrna::new("GGGUAA")), None);
+    }
+    #[test]
+    fn test_chemistry() {
+        let glycine = Polypeptide::from_one_letter("G").unwrap();
+        assert!((glycine.mass() - 75.07).abs() < 0.01);
+        let dipeptide = Polypeptide::from_one_letter("GG").unwrap();
+        assert!((dipeptide.mass() - (2.0 * 75.07 - 18.015)).abs() < 0.01);
+
+        let chain = Polypeptide::from_one_letter("IVL").unwrap();
+        assert!((chain.gravy() - (4.5 + 4.2 + 3.8) / 3.0).abs() < 0.001);
+
+        let acidic = Polypeptide::from_one_letter("DDEE").unwrap();
+        let basic = Polypeptide::from_one_letter("KKRR").unwrap();
+        assert!(acidic.isoelectric_point() < 4.5);
+        assert!(basic.isoelectric_point() > 10.0);
+        assert!(acidic.charge_at(7.0) < 0.0);
+
+        assert_eq!(AminoAcid::from_char('W'), Some(AminoAcid::Tryptophan));
+        assert_eq!(AminoAcid::Lysine.charge(), 1);
+        assert_eq!(
+            AminoAcid::GlutamicAcid.side_chain_class(),
+            SideChainClass::Acidic
+        );
+        assert_eq!(Polypeptide::from_one_letter("MX"), None);
+    }
+}
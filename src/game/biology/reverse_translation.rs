@@ -0,0 +1,122 @@
+//! Encode text back into an mRNA, the inverse of [`Mrna::to_string_toki`].
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use thiserror::Error;
+
+use super::{translation::NucleobaseRnaToAminoChar, Codon, Mrna, CODON_TABLE};
+
+/// How to pick between synonymous codons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodonChoice {
+    /// The first codon in chart order (U, C, A, G).
+    First,
+    /// A random codon, the same seed gives the same mRNA.
+    Random {
+        seed: u64,
+    },
+    LowestGc,
+    HighestGc,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReverseTranslationError {
+    #[error("'{c}' at position {position} is not produced by any codon")]
+    Untranslatable { position: usize, c: char },
+}
+
+impl NucleobaseRnaToAminoChar {
+    /// Every codon that translates to `c`, in chart order.
+    pub fn codons_for(&self, c: char) -> Vec<Codon> {
+        (0..64)
+            .map(Codon::from_index)
+            .filter(|codon| self.translation(codon) == c)
+            .collect()
+    }
+
+    pub fn reverse_translate(
+        &self,
+        text: &str,
+        choice: CodonChoice,
+    ) -> Result<Mrna, ReverseTranslationError> {
+        let seed = match choice {
+            CodonChoice::Random { seed } => seed,
+            _ => 0,
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut mrna = Mrna::default();
+        for (position, c) in text.chars().enumerate() {
+            let codons = self.codons_for(c);
+            let codon = match choice {
+                CodonChoice::First => codons.first().copied(),
+                CodonChoice::Random { .. } => codons.choose(&mut rng).copied(),
+                CodonChoice::LowestGc => codons.iter().min_by_key(|c| c.gc_count()).copied(),
+                // max_by_key keeps the last of equal codons, rev keeps chart order for ties
+                CodonChoice::HighestGc => codons.iter().rev().max_by_key(|c| c.gc_count()).copied(),
+            };
+            match codon {
+                Some(codon) => mrna.push(codon),
+                None => return Err(ReverseTranslationError::Untranslatable { position, c }),
+            }
+        }
+        Ok(mrna)
+    }
+}
+
+impl Mrna {
+    /// Build an mRNA that [`Mrna::to_string_toki`] turns back into `text`.
+    /// Always Toki Pona, other codes have [`NucleobaseRnaToAminoChar::reverse_translate`].
+    pub fn from_toki(text: &str, choice: CodonChoice) -> Result<Self, ReverseTranslationError> {
+        CODON_TABLE.reverse_translate(text, choice)
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[test]
+    fn test_round_trip() {
+        let text = "^Toki Pona.";
+        for choice in [
+            CodonChoice::First,
+            CodonChoice::Random { seed: 5 },
+            CodonChoice::LowestGc,
+            CodonChoice::HighestGc,
+        ] {
+            let mrna = Mrna::from_toki(text, choice).unwrap();
+            assert_eq!(mrna.to_string_toki(), text, "{:?}", choice);
+        }
+    }
+    #[test]
+    fn test_choices() {
+        assert_eq!(
+            Mrna::from_toki("s", CodonChoice::First).unwrap(),
+            Mrna::new("UCC")
+        );
+        assert_eq!(
+            Mrna::from_toki("s", CodonChoice::LowestGc).unwrap(),
+            Mrna::new("UCA")
+        );
+        assert_eq!(
+            Mrna::from_toki("k", CodonChoice::LowestGc).unwrap(),
+            Mrna::new("CGA")
+        );
+        assert_eq!(
+            Mrna::from_toki("k", CodonChoice::HighestGc).unwrap(),
+            Mrna::new("CGG")
+        );
+        assert_eq!(
+            Mrna::from_toki("sssss", CodonChoice::Random { seed: 7 }),
+            Mrna::from_toki("sssss", CodonChoice::Random { seed: 7 })
+        );
+    }
+    #[test]
+    fn test_untranslatable() {
+        assert_eq!(
+            Mrna::from_toki("^Toki Z", CodonChoice::First),
+            Err(ReverseTranslationError::Untranslatable {
+                position: 6,
+                c: 'Z'
+            })
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! A ribosome that walks along a copy of the mRNA codon by codon.
+//! Each step a tRNA with the pairing anticodon flies in and the chain grows by one glyph,
+//! a stop codon releases the chain.
+
+use std::time::Duration;
+
+use bevy::{color::palettes::css::SEA_GREEN, prelude::*, render::view::visibility::RenderLayers};
+
+use super::{
+    lifecycle::Deadenylation,
+    translation::NucleobaseRnaToAminoChar,
+    trna::{decoding_trnas, minimal_trna_set, Anticodon, TransferRna},
+    Mrna,
+};
+use crate::{
+    game::assets::{HandleMap, Scene3dKey},
+    screen::Screen,
+    AppSet,
+};
+
+const CODON_SPACING: f32 = 1.5;
+const STRAND_ORIGIN: Vec3 = Vec3::new(-12.0, -12.0, 0.0);
+const CHAIN_OFFSET: Vec3 = Vec3::new(0.0, 2.5, 0.0);
+const TRNA_OFFSET: Vec3 = Vec3::new(0.0, 6.0, 0.0);
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RibosomeSettings>();
+    app.init_resource::<RibosomeSettings>();
+    app.observe(spawn_ribosome);
+    app.add_systems(
+        Update,
+        (tick_ribosomes, tick_trnas, tick_released_chains).in_set(AppSet::TickTimers),
+    );
+    app.add_systems(
+        Update,
+        (
+            degrade_ribosome_mrna,
+            step_ribosomes,
+            move_trnas,
+            drift_released_chains,
+            draw_ribosome_mrna,
+        )
+            .chain()
+            .in_set(AppSet::Update),
+    );
+}
+
+/// How fast ribosomes read, shared by all of them.
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct RibosomeSettings {
+    pub codons_per_second: f32,
+    /// Seconds a released chain drifts before it despawns.
+    pub release_seconds: f32,
+}
+
+impl Default for RibosomeSettings {
+    fn default() -> Self {
+        Self {
+            codons_per_second: 2.0,
+            release_seconds: 4.0,
+        }
+    }
+}
+
+impl RibosomeSettings {
+    fn step(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.codons_per_second.max(0.01))
+    }
+}
+
+/// Trigger this event to translate `mrna` with `table`,
+/// scanning for a start codon from codon `from`.
+#[derive(Event, Debug)]
+pub struct SpawnRibosome {
+    pub mrna: Mrna,
+    pub table: NucleobaseRnaToAminoChar,
+    pub from: usize,
+}
+
+#[derive(Component, Debug)]
+pub struct Ribosome {
+    mrna: Mrna,
+    /// The code active when translation began, switching codes doesn't affect it.
+    table: NucleobaseRnaToAminoChar,
+    /// The tRNAs available to it, the smallest set for `table`.
+    trnas: Vec<TransferRna>,
+    /// Index of the codon in the ribosome's A site.
+    position: usize,
+    translating: bool,
+    timer: Timer,
+    chain: Entity,
+    length: usize,
+}
+
+/// The growing chain, parent of one glyph per residue.
+#[derive(Component, Debug)]
+pub struct PolypeptideChain;
+
+#[derive(Component, Debug)]
+pub struct ReleasedChain {
+    timer: Timer,
+}
+
+#[derive(Component, Debug)]
+pub struct Trna {
+    pub anticodon: Anticodon,
+    pub amino: char,
+    target: Vec3,
+    timer: Timer,
+}
+
+fn codon_translation(index: usize) -> Vec3 {
+    STRAND_ORIGIN + Vec3::X * CODON_SPACING * index as f32
+}
+
+fn spawn_ribosome(
+    trigger: Trigger<SpawnRibosome>,
+    mut commands: Commands,
+    settings: Res<RibosomeSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let event = trigger.event();
+    if !event.mrna.is_capped() {
+        warn!("ribosomes only load onto capped mRNA");
+        return;
+    }
+    let from = event.from.min(event.mrna.codons().len());
+    let chain = commands
+        .spawn((
+            Name::new("PolypeptideChain"),
+            PolypeptideChain,
+            SpatialBundle::from_transform(Transform::from_translation(
+                codon_translation(from) + CHAIN_OFFSET,
+            )),
+            RenderLayers::layer(1),
+            StateScoped(Screen::Playing),
+        ))
+        .id();
+    commands.spawn((
+        Name::new("Ribosome"),
+        Ribosome {
+            mrna: event.mrna.clone(),
+            table: event.table.clone(),
+            trnas: minimal_trna_set(&event.table),
+            position: from,
+            translating: false,
+            timer: Timer::new(settings.step(), TimerMode::Repeating),
+            chain,
+            length: 0,
+        },
+        PbrBundle {
+            mesh: meshes.add(Sphere::new(1.2)),
+            material: materials.add(Color::from(SEA_GREEN)),
+            transform: Transform::from_translation(codon_translation(from)),
+            ..default()
+        },
+        RenderLayers::layer(1),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn tick_ribosomes(
+    time: Res<Time>,
+    settings: Res<RibosomeSettings>,
+    mut ribosome_q: Query<&mut Ribosome>,
+) {
+    for mut ribosome in &mut ribosome_q {
+        if settings.is_changed() {
+            ribosome.timer.set_duration(settings.step());
+        }
+        ribosome.timer.tick(time.delta());
+    }
+}
+
+fn tick_trnas(time: Res<Time>, mut trna_q: Query<&mut Trna>) {
+    for mut trna in &mut trna_q {
+        trna.timer.tick(time.delta());
+    }
+}
+
+fn tick_released_chains(time: Res<Time>, mut chain_q: Query<&mut ReleasedChain>) {
+    for mut chain in &mut chain_q {
+        chain.timer.tick(time.delta());
+    }
+}
+
+/// Each ribosome's copy decays like the original, taking the unfinished chain with it.
+fn degrade_ribosome_mrna(
+    mut commands: Commands,
+    deadenylation: Res<Deadenylation>,
+    mut ribosome_q: Query<(Entity, &mut Ribosome)>,
+) {
+    let removed = deadenylation.removed();
+    if removed == 0 {
+        return;
+    }
+    for (entity, mut ribosome) in &mut ribosome_q {
+        if ribosome.mrna.deadenylate(removed) {
+            commands.entity(ribosome.chain).despawn_recursive();
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn step_ribosomes(
+    mut commands: Commands,
+    settings: Res<RibosomeSettings>,
+    scene_handles: Res<HandleMap<Scene3dKey>>,
+    mut ribosome_q: Query<(Entity, &mut Ribosome, &mut Transform)>,
+) {
+    for (entity, mut ribosome, mut transform) in &mut ribosome_q {
+        if !ribosome.timer.just_finished() {
+            continue;
+        }
+        let Some(codon) = ribosome.mrna.codons().get(ribosome.position).copied() else {
+            // ran off the end without a stop, the unfinished chain goes with it
+            commands.entity(ribosome.chain).despawn_recursive();
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = codon_translation(ribosome.position);
+        if !ribosome.translating && !ribosome.table.is_start(&codon) {
+            // still scanning for a start codon
+            ribosome.position += 1;
+            continue;
+        }
+        ribosome.translating = true;
+        if ribosome.table.is_stop(&codon) {
+            commands.entity(ribosome.chain).insert(ReleasedChain {
+                timer: Timer::from_seconds(settings.release_seconds, TimerMode::Once),
+            });
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let amino = ribosome.table.translation(&codon);
+        // a wobble tRNA if one carries the right character, otherwise an exact match
+        let anticodon = decoding_trnas(&ribosome.trnas, &codon)
+            .into_iter()
+            .find(|trna| trna.amino == amino)
+            .map_or_else(|| Anticodon::pairing(&codon), |trna| trna.anticodon);
+        let trna = Trna {
+            anticodon,
+            amino,
+            target: transform.translation,
+            timer: Timer::new(settings.step(), TimerMode::Once),
+        };
+        commands.spawn((
+            Name::new(format!("tRNA {} {}", trna.anticodon, trna.amino)),
+            trna,
+            SceneBundle {
+                scene: glyph(&scene_handles, &ribosome.table, amino),
+                transform: Transform::from_translation(transform.translation + TRNA_OFFSET),
+                ..default()
+            },
+            RenderLayers::layer(1),
+            StateScoped(Screen::Playing),
+        ));
+        let residue = commands
+            .spawn((
+                Name::new(amino.to_string()),
+                SceneBundle {
+                    scene: glyph(&scene_handles, &ribosome.table, amino),
+                    transform: Transform::from_translation(
+                        Vec3::X * CODON_SPACING * ribosome.length as f32,
+                    ),
+                    ..default()
+                },
+                RenderLayers::layer(1),
+            ))
+            .id();
+        commands.entity(ribosome.chain).add_child(residue);
+        ribosome.length += 1;
+        ribosome.position += 1;
+    }
+}
+
+/// A glyph for the residue, characters without a model (like a space) spawn empty.
+fn glyph(
+    scene_handles: &HandleMap<Scene3dKey>,
+    table: &NucleobaseRnaToAminoChar,
+    c: char,
+) -> Handle<Scene> {
+    table
+        .scene_3d_key(c)
+        .map(|key| scene_handles[&key].clone_weak())
+        .unwrap_or_default()
+}
+
+fn move_trnas(mut commands: Commands, mut trna_q: Query<(Entity, &Trna, &mut Transform)>) {
+    for (entity, trna, mut transform) in &mut trna_q {
+        if trna.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let from = trna.target + TRNA_OFFSET;
+        transform.translation = from.lerp(trna.target, trna.timer.fraction());
+    }
+}
+
+fn drift_released_chains(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut chain_q: Query<(Entity, &ReleasedChain, &mut Transform)>,
+) {
+    for (entity, chain, mut transform) in &mut chain_q {
+        if chain.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += 2.0 * time.delta_seconds();
+    }
+}
+
+/// Draw the strand each ribosome is reading, with a tick between codons.
+fn draw_ribosome_mrna(ribosome_q: Query<&Ribosome>, mut gizmos: Gizmos) {
+    for ribosome in &ribosome_q {
+        let count = ribosome.mrna.codons().len();
+        let half = Vec3::X * CODON_SPACING / 2.0;
+        let start = codon_translation(0) - half;
+        let end = codon_translation(count) - half;
+        gizmos.line(start, end, Color::WHITE);
+        for i in 0..=count {
+            let tick = codon_translation(i) - half;
+            gizmos.line(tick, tick - Vec3::Y * 0.5, Color::WHITE);
+        }
+    }
+}
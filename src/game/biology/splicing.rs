@@ -0,0 +1,332 @@
+//! Pre-mRNA with introns, and splicing it into mature [`Mrna`].
+//! Introns are marked by hand (lower case in [`PreMrna::from_marked`]) or found
+//! with [`PreMrna::detect`] from the canonical `GU…AG` ends and a branch point.
+
+use thiserror::Error;
+
+use super::{parse::SequenceError, Mrna, NucleobaseRna};
+
+/// Shorter introns can't bend round to the branch point.
+pub const MIN_INTRON_LENGTH: usize = 24;
+/// How far upstream of the 3' `AG` the branch point adenine may sit.
+pub const BRANCH_POINT_DISTANCE: std::ops::RangeInclusive<usize> = 18..=40;
+/// Exon skipping combinations grow as 2^n, more internal exons than this aren't enumerated.
+pub const MAX_SKIPPABLE_EXONS: usize = 10;
+
+/// Bases `start..end` of the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intron {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Intron {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum SplicingError {
+    #[error("intron {start}..{end} is outside the {length} bases")]
+    OutOfRange {
+        start: usize,
+        end: usize,
+        length: usize,
+    },
+    #[error("intron {start}..{end} is empty or overlaps the one before it")]
+    Overlap { start: usize, end: usize },
+    #[error("exon {exon} doesn't exist, there are {count}")]
+    NoSuchExon { exon: usize, count: usize },
+}
+
+/// A transcript before splicing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PreMrna {
+    bases: Vec<NucleobaseRna>,
+    /// In order and not overlapping.
+    introns: Vec<Intron>,
+}
+
+/// One way of joining the exons of a [`PreMrna`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isoform {
+    /// Indices of the exons kept, in order.
+    pub exons: Vec<usize>,
+    pub mrna: Mrna,
+}
+
+impl PreMrna {
+    pub fn with_introns(
+        bases: Vec<NucleobaseRna>,
+        mut introns: Vec<Intron>,
+    ) -> Result<Self, SplicingError> {
+        introns.sort_by_key(|intron| intron.start);
+        let mut previous_end = 0;
+        for intron in &introns {
+            if intron.end > bases.len() {
+                return Err(SplicingError::OutOfRange {
+                    start: intron.start,
+                    end: intron.end,
+                    length: bases.len(),
+                });
+            }
+            if intron.is_empty() || intron.start > intron.end || intron.start < previous_end {
+                return Err(SplicingError::Overlap {
+                    start: intron.start,
+                    end: intron.end,
+                });
+            }
+            previous_end = intron.end;
+        }
+        Ok(PreMrna { bases, introns })
+    }
+
+    /// Exons in upper case, introns in lower case, like `"AUGguaag…cagACU"`.
+    pub fn from_marked(s: &str) -> Result<Self, SequenceError> {
+        let mut bases = Vec::new();
+        let mut introns = Vec::new();
+        let mut intron_start = None;
+        for (position, c) in s.chars().enumerate() {
+            let base = NucleobaseRna::from_char(c.to_ascii_uppercase())
+                .ok_or(SequenceError::InvalidBase { position, c })?;
+            match (c.is_ascii_lowercase(), intron_start) {
+                (true, None) => intron_start = Some(position),
+                (false, Some(start)) => {
+                    introns.push(Intron {
+                        start,
+                        end: position,
+                    });
+                    intron_start = None;
+                }
+                _ => {}
+            }
+            bases.push(base);
+        }
+        if let Some(start) = intron_start {
+            introns.push(Intron {
+                start,
+                end: bases.len(),
+            });
+        }
+        Ok(PreMrna { bases, introns })
+    }
+
+    /// Find introns: a `GU` at the 5' end, an `AG` at the 3' end and an adenine branch point
+    /// [`BRANCH_POINT_DISTANCE`] upstream of it, followed by a pyrimidine rich tract.
+    /// The shortest such intron from each `GU` is taken, scanning from the start.
+    pub fn detect(bases: Vec<NucleobaseRna>) -> Self {
+        use NucleobaseRna::*;
+        let mut introns = Vec::new();
+        let mut start = 0;
+        while start + MIN_INTRON_LENGTH <= bases.len() {
+            if bases[start..start + 2] != [Guanine, Uracil] {
+                start += 1;
+                continue;
+            }
+            let end = (start + MIN_INTRON_LENGTH..=bases.len()).find(|&end| {
+                bases[end - 2..end] == [Adenine, Guanine] && has_branch_point(&bases[start..end])
+            });
+            match end {
+                Some(end) => {
+                    introns.push(Intron { start, end });
+                    start = end;
+                }
+                None => start += 1,
+            }
+        }
+        PreMrna { bases, introns }
+    }
+
+    pub fn bases(&self) -> &[NucleobaseRna] {
+        &self.bases
+    }
+    pub fn introns(&self) -> &[Intron] {
+        &self.introns
+    }
+
+    /// The stretches between introns, possibly empty when introns touch the ends.
+    pub fn exons(&self) -> Vec<std::ops::Range<usize>> {
+        let mut exons = Vec::with_capacity(self.introns.len() + 1);
+        let mut start = 0;
+        for intron in &self.introns {
+            exons.push(start..intron.start);
+            start = intron.end;
+        }
+        exons.push(start..self.bases.len());
+        exons
+    }
+
+    /// Cut out every intron, a trailing partial codon is dropped.
+    pub fn splice(&self) -> Mrna {
+        let all: Vec<usize> = (0..self.exons().len()).collect();
+        self.splice_exons(&all)
+            .expect("every exon index is in range")
+    }
+
+    /// Join only the exons with these indices, in the order given.
+    pub fn splice_exons(&self, exons: &[usize]) -> Result<Mrna, SplicingError> {
+        let ranges = self.exons();
+        let mut bases = Vec::new();
+        for &exon in exons {
+            let range = ranges.get(exon).ok_or(SplicingError::NoSuchExon {
+                exon,
+                count: ranges.len(),
+            })?;
+            bases.extend_from_slice(&self.bases[range.clone()]);
+        }
+        Ok(Mrna::from_bases(&bases))
+    }
+
+    /// Every exon skipping isoform: the first and last exon are always kept,
+    /// each internal exon may be left out. The fully spliced mRNA comes first.
+    /// Only the first [`MAX_SKIPPABLE_EXONS`] internal exons are varied.
+    pub fn isoforms(&self) -> Vec<Isoform> {
+        let count = self.exons().len();
+        if count <= 2 {
+            let exons: Vec<usize> = (0..count).collect();
+            return vec![Isoform {
+                mrna: self.splice(),
+                exons,
+            }];
+        }
+        let internal: Vec<usize> = (1..count - 1).collect();
+        let varied = internal.len().min(MAX_SKIPPABLE_EXONS);
+        (0u32..1 << varied)
+            .map(|skipped| {
+                let mut exons = vec![0];
+                for (i, exon) in internal.iter().enumerate() {
+                    if i >= varied || skipped & (1 << i) == 0 {
+                        exons.push(*exon);
+                    }
+                }
+                exons.push(count - 1);
+                Isoform {
+                    mrna: self
+                        .splice_exons(&exons)
+                        .expect("every exon index is in range"),
+                    exons,
+                }
+            })
+            .collect()
+    }
+}
+
+/// An adenine the right distance before the 3' end with mostly pyrimidines after it.
+fn has_branch_point(intron: &[NucleobaseRna]) -> bool {
+    let end = intron.len() - 2;
+    BRANCH_POINT_DISTANCE.any(|distance| {
+        let Some(branch) = end.checked_sub(distance) else {
+            return false;
+        };
+        // keep clear of the 5' GU
+        if branch < 2 || intron[branch] != NucleobaseRna::Adenine {
+            return false;
+        }
+        let tract = &intron[branch + 1..end];
+        let pyrimidines = tract
+            .iter()
+            .filter(|b| matches!(b, NucleobaseRna::Uracil | NucleobaseRna::Cytosine))
+            .count();
+        pyrimidines * 2 >= tract.len()
+    })
+}
+
+impl std::fmt::Display for PreMrna {
+    /// The [`PreMrna::from_marked`] form.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, base) in self.bases.iter().enumerate() {
+            let in_intron = self.introns.iter().any(|n| (n.start..n.end).contains(&i));
+            let c = base.to_char();
+            write!(f, "{}", if in_intron { c.to_ascii_lowercase() } else { c })?;
+        }
+        Ok(())
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::parse::parse_rna_bases;
+
+    /// GU, filler, a branch point A, a pyrimidine tract and AG.
+    #[allow(dead_code)]
+    const INTRON: &str = "guaaggaaggaaacuaacuuucucuuuuucccuuucag";
+
+    #[test]
+    fn test_marked() {
+        // ^Toki Pona. with an intron in the middle of "Toki"
+        let pre =
+            PreMrna::from_marked(&format!("AUGACUCAG{INTRON}CGAAUAGUUCCUCAGAACGCGUGA")).unwrap();
+        assert_eq!(pre.introns().len(), 1);
+        assert_eq!(pre.splice().to_string_toki(), "^Toki Pona.");
+        assert_ne!(
+            Mrna::from_bases(pre.bases()).to_string_toki(),
+            "^Toki Pona."
+        );
+        assert_eq!(
+            pre.to_string(),
+            format!("AUGACUCAG{INTRON}CGAAUAGUUCCUCAGAACGCGUGA")
+        );
+    }
+
+    #[test]
+    fn test_detect() {
+        let marked = format!("AUGACUCAG{INTRON}CGAAUAGUUCCUCAGAACGCGUGA");
+        let bases = parse_rna_bases(&marked.to_ascii_uppercase()).unwrap();
+        assert_eq!(
+            PreMrna::detect(bases),
+            PreMrna::from_marked(&marked).unwrap()
+        );
+        // no branch point, nothing to cut
+        let bases = parse_rna_bases("AUGGUCCCCCCCCCCCCCCCCCCCCCCCCCCCAGUAA").unwrap();
+        assert!(PreMrna::detect(bases).introns().is_empty());
+    }
+
+    #[test]
+    fn test_isoforms() {
+        // exons: AUG, ACU, CAG, then the rest
+        let pre = PreMrna::from_marked(&format!(
+            "AUG{INTRON}ACU{INTRON}CAG{INTRON}CGAAUAGUUCCUCAGAACGCGUGA"
+        ))
+        .unwrap();
+        assert_eq!(pre.exons().len(), 4);
+        let isoforms = pre.isoforms();
+        assert_eq!(isoforms.len(), 4);
+        assert_eq!(isoforms[0].exons, [0, 1, 2, 3]);
+        assert_eq!(isoforms[0].mrna.to_string_toki(), "^Toki Pona.");
+        let skipped: Vec<String> = isoforms.iter().map(|i| i.mrna.to_string_toki()).collect();
+        assert!(skipped.contains(&"^ki Pona.".to_string()));
+        assert!(skipped.contains(&"^oki Pona.".to_string()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let bases = parse_rna_bases("AUGGUAAGUAA").unwrap();
+        assert_eq!(
+            PreMrna::with_introns(bases.clone(), vec![Intron { start: 3, end: 20 }]),
+            Err(SplicingError::OutOfRange {
+                start: 3,
+                end: 20,
+                length: 11
+            })
+        );
+        assert_eq!(
+            PreMrna::with_introns(
+                bases.clone(),
+                vec![Intron { start: 3, end: 6 }, Intron { start: 5, end: 8 }]
+            ),
+            Err(SplicingError::Overlap { start: 5, end: 8 })
+        );
+        let pre = PreMrna::with_introns(bases, vec![Intron { start: 3, end: 8 }]).unwrap();
+        assert_eq!(pre.splice(), Mrna::new("AUGUAA"));
+        assert_eq!(
+            pre.splice_exons(&[2]),
+            Err(SplicingError::NoSuchExon { exon: 2, count: 2 })
+        );
+    }
+}
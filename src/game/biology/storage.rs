@@ -0,0 +1,515 @@
+//! Store arbitrary bytes as bases, like
+//! [Goldman et al. 2013](https://doi.org/10.1038/nature11875) did with Shakespeare's sonnets.
+//! Bytes are protected by a Reed–Solomon code in blocks of up to 255, then every byte is written
+//! as six base-3 digits. Each digit picks one of the three bases that differ from the one before,
+//! so the sequence never repeats a base, which sequencers misread.
+//! Substitutions are corrected up to half the parity bytes per block,
+//! an insertion or deletion shifts everything after it and is not.
+//! F3 hides the [`SecretMessage`] in the selected mRNA in free play,
+//! F4 reads back whatever it holds.
+
+use bevy::prelude::*;
+use thiserror::Error;
+
+use super::{
+    codon_table::ActiveCodonTable,
+    editor::sequence,
+    history::{Edit, EditHistory, HistorySettings},
+    release_new_products, CodonState, LogTextRes, Mrna, NucleobaseRna, SelectedMrna,
+};
+use crate::{game::puzzle::in_free_play, screen::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SecretMessage>();
+    app.init_resource::<SecretMessage>();
+    app.add_systems(
+        Update,
+        (
+            // it would replace a level's mRNA, limits and all
+            hide_secret_message.run_if(in_free_play),
+            read_secret_message,
+        )
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// What F3 writes into a cell.
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct SecretMessage {
+    pub text: String,
+    /// Reed–Solomon parity bytes per block, see [`StorageCodec::parity`].
+    pub parity: usize,
+}
+
+impl SecretMessage {
+    fn codec(&self) -> StorageCodec {
+        StorageCodec {
+            parity: self.parity.clamp(1, BLOCK_LEN - 1),
+        }
+    }
+}
+
+impl Default for SecretMessage {
+    fn default() -> Self {
+        Self {
+            text: "sona len li lon insa".to_string(),
+            parity: 16,
+        }
+    }
+}
+
+/// 3^6 = 729 covers the 256 byte values.
+pub const BASES_PER_BYTE: usize = 6;
+/// Longest Reed–Solomon block over GF(256), data and parity together.
+const BLOCK_LEN: usize = 255;
+/// The base assumed before the first one, for the first rotation.
+const FIRST_PREVIOUS: NucleobaseRna = NucleobaseRna::Adenine;
+/// Rotation order, a digit is the index among these with the previous base left out.
+const ROTATION: [NucleobaseRna; 4] = [
+    NucleobaseRna::Uracil,
+    NucleobaseRna::Cytosine,
+    NucleobaseRna::Adenine,
+    NucleobaseRna::Guanine,
+];
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum StorageError {
+    #[error("{length} bases are not whole blocks of {BASES_PER_BYTE} per byte")]
+    Length { length: usize },
+    #[error("block {block} has more errors than its parity can correct")]
+    TooManyErrors { block: usize },
+    #[error("the message is not utf-8")]
+    Utf8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageCodec {
+    /// Reed–Solomon parity bytes per block, corrects half as many bad bytes.
+    pub parity: usize,
+}
+
+impl Default for StorageCodec {
+    fn default() -> Self {
+        Self { parity: 16 }
+    }
+}
+
+impl StorageCodec {
+    /// Data bytes per block.
+    fn data_len(&self) -> usize {
+        assert!(
+            (1..BLOCK_LEN).contains(&self.parity),
+            "parity must be 1 to 254 bytes, got {}",
+            self.parity
+        );
+        BLOCK_LEN - self.parity
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Vec<NucleobaseRna> {
+        let mut bytes = Vec::new();
+        for block in data.chunks(self.data_len()) {
+            bytes.extend(rs::encode(block, self.parity));
+        }
+        let mut bases = Vec::with_capacity(bytes.len() * BASES_PER_BYTE);
+        let mut previous = FIRST_PREVIOUS;
+        for byte in bytes {
+            let mut trits = [0; BASES_PER_BYTE];
+            let mut value = byte as usize;
+            for trit in trits.iter_mut().rev() {
+                *trit = value % 3;
+                value /= 3;
+            }
+            for trit in trits {
+                previous = rotate(previous, trit);
+                bases.push(previous);
+            }
+        }
+        bases
+    }
+
+    pub fn decode(&self, bases: &[NucleobaseRna]) -> Result<Vec<u8>, StorageError> {
+        let length = bases.len();
+        if length % BASES_PER_BYTE != 0 {
+            return Err(StorageError::Length { length });
+        }
+        let mut bytes = Vec::with_capacity(length / BASES_PER_BYTE);
+        let mut previous = FIRST_PREVIOUS;
+        for chunk in bases.chunks_exact(BASES_PER_BYTE) {
+            let mut value = 0;
+            for base in chunk {
+                value = value * 3 + unrotate(previous, *base);
+                previous = *base;
+            }
+            // a mutation can give up to 728, that byte is wrong either way
+            bytes.push(u8::try_from(value).unwrap_or(0));
+        }
+        let mut data = Vec::with_capacity(bytes.len());
+        for (block, codeword) in bytes.chunks(BLOCK_LEN).enumerate() {
+            if codeword.len() <= self.parity {
+                return Err(StorageError::Length { length });
+            }
+            let corrected =
+                rs::decode(codeword, self.parity).ok_or(StorageError::TooManyErrors { block })?;
+            data.extend_from_slice(&corrected[..codeword.len() - self.parity]);
+        }
+        Ok(data)
+    }
+
+    pub fn encode_str(&self, text: &str) -> Vec<NucleobaseRna> {
+        self.encode(text.as_bytes())
+    }
+    pub fn decode_string(&self, bases: &[NucleobaseRna]) -> Result<String, StorageError> {
+        String::from_utf8(self.decode(bases)?).map_err(|_| StorageError::Utf8)
+    }
+
+    /// A message to hide in a cell, six bases a byte is always whole codons.
+    pub fn encode_mrna(&self, text: &str) -> Mrna {
+        Mrna::from_bases(&self.encode_str(text))
+    }
+    pub fn decode_mrna(&self, mrna: &Mrna) -> Result<String, StorageError> {
+        self.decode_string(&mrna.bases())
+    }
+}
+
+/// Replaces the selected mRNA, typed bases included, as one undoable step.
+/// Any product the message happens to spell is released.
+fn hide_secret_message(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    secret: Res<SecretMessage>,
+    settings: Res<HistorySettings>,
+    active_table: Res<ActiveCodonTable>,
+    mut mrna_q: Query<(Entity, &mut Mrna, &mut CodonState, &mut EditHistory), With<SelectedMrna>>,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    let Ok((entity, mut mrna, mut codon_state, mut history)) = mrna_q.get_single_mut() else {
+        return;
+    };
+    let codec = secret.codec();
+    let after = codec.encode_str(&secret.text);
+    log_text.text = format!(
+        "{}\nHid {:?} in {} bases",
+        log_text.text,
+        secret.text,
+        after.len()
+    );
+    let before = sequence(&mrna, &codon_state);
+    let unedited = mrna.clone();
+    history.perform(
+        vec![Edit::Replace { before, after }],
+        &mut mrna,
+        &mut codon_state,
+        settings.limit,
+    );
+    if release_new_products(&mut commands, entity, &unedited, &mrna, &active_table.table) {
+        history.mark_translated();
+    }
+}
+
+fn read_secret_message(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    secret: Res<SecretMessage>,
+    mrna_q: Query<(&Mrna, &CodonState), With<SelectedMrna>>,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F4) {
+        return;
+    }
+    let Ok((mrna, codon_state)) = mrna_q.get_single() else {
+        return;
+    };
+    let codec = secret.codec();
+    let message = match codec.decode_string(&sequence(mrna, codon_state)) {
+        Ok(text) => format!("The mRNA says {text:?}"),
+        Err(e) => format!("No message: {e}"),
+    };
+    log_text.text = format!("{}\n{}", log_text.text, message);
+}
+
+/// The base for `trit` after `previous`.
+fn rotate(previous: NucleobaseRna, trit: usize) -> NucleobaseRna {
+    ROTATION
+        .into_iter()
+        .filter(|b| *b != previous)
+        .nth(trit)
+        .unwrap()
+}
+
+/// The digit `base` stands for after `previous`, a repeated base reads as 0.
+fn unrotate(previous: NucleobaseRna, base: NucleobaseRna) -> usize {
+    ROTATION
+        .into_iter()
+        .filter(|b| *b != previous)
+        .position(|b| b == base)
+        .unwrap_or(0)
+}
+
+/// Reed–Solomon over GF(2^8) with the polynomial 0x11d and first root 1, the layout of
+/// [Reed–Solomon codes for coders](https://en.wikiversity.org/wiki/Reed%E2%80%93Solomon_codes_for_coders).
+/// Polynomials are highest degree first, codewords are the data followed by the parity.
+mod rs {
+    struct Tables {
+        exp: [u8; 512],
+        log: [u8; 256],
+    }
+
+    const TABLES: Tables = {
+        let mut exp = [0; 512];
+        let mut log = [0; 256];
+        let mut x: u16 = 1;
+        let mut i = 0;
+        while i < 255 {
+            exp[i] = x as u8;
+            log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= 0x11d;
+            }
+            i += 1;
+        }
+        while i < 512 {
+            exp[i] = exp[i - 255];
+            i += 1;
+        }
+        Tables { exp, log }
+    };
+
+    fn mul(a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        TABLES.exp[TABLES.log[a as usize] as usize + TABLES.log[b as usize] as usize]
+    }
+    fn div(a: u8, b: u8) -> u8 {
+        if a == 0 {
+            return 0;
+        }
+        TABLES.exp[(TABLES.log[a as usize] as usize + 255 - TABLES.log[b as usize] as usize) % 255]
+    }
+    /// `a` to the power `n`, negative powers too.
+    fn pow(a: u8, n: i32) -> u8 {
+        TABLES.exp[(TABLES.log[a as usize] as i32 * n).rem_euclid(255) as usize]
+    }
+    fn inverse(a: u8) -> u8 {
+        TABLES.exp[255 - TABLES.log[a as usize] as usize]
+    }
+
+    fn poly_scale(p: &[u8], x: u8) -> Vec<u8> {
+        p.iter().map(|c| mul(*c, x)).collect()
+    }
+    /// Sum with the lower degrees lined up on the right.
+    fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+        let len = p.len().max(q.len());
+        let mut r = vec![0; len];
+        for (i, c) in p.iter().enumerate() {
+            r[i + len - p.len()] = *c;
+        }
+        for (i, c) in q.iter().enumerate() {
+            r[i + len - q.len()] ^= *c;
+        }
+        r
+    }
+    fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+        let mut r = vec![0; p.len() + q.len() - 1];
+        for (j, b) in q.iter().enumerate() {
+            for (i, a) in p.iter().enumerate() {
+                r[i + j] ^= mul(*a, *b);
+            }
+        }
+        r
+    }
+    fn poly_eval(p: &[u8], x: u8) -> u8 {
+        p.iter().fold(0, |y, c| mul(y, x) ^ c)
+    }
+    /// The remainder of dividing by a monic `divisor`, `divisor.len() - 1` terms at most.
+    fn poly_rem(dividend: &[u8], divisor: &[u8]) -> Vec<u8> {
+        let mut out = dividend.to_vec();
+        let degree = divisor.len() - 1;
+        for i in 0..dividend.len().saturating_sub(degree) {
+            let coef = out[i];
+            if coef != 0 {
+                for (j, d) in divisor.iter().enumerate().skip(1) {
+                    out[i + j] ^= mul(*d, coef);
+                }
+            }
+        }
+        out.split_off(out.len().saturating_sub(degree))
+    }
+
+    fn generator(parity: usize) -> Vec<u8> {
+        (0..parity).fold(vec![1], |g, i| poly_mul(&g, &[1, pow(2, i as i32)]))
+    }
+
+    pub fn encode(data: &[u8], parity: usize) -> Vec<u8> {
+        let mut padded = data.to_vec();
+        padded.resize(data.len() + parity, 0);
+        let mut codeword = data.to_vec();
+        codeword.extend(poly_rem(&padded, &generator(parity)));
+        codeword
+    }
+
+    /// With a leading 0, so index `i + 1` is the codeword at α^i.
+    fn syndromes(codeword: &[u8], parity: usize) -> Vec<u8> {
+        let mut synd = vec![0];
+        synd.extend((0..parity).map(|i| poly_eval(codeword, pow(2, i as i32))));
+        synd
+    }
+
+    /// Berlekamp–Massey, `None` if there are more errors than the parity can locate.
+    fn error_locator(synd: &[u8], parity: usize) -> Option<Vec<u8>> {
+        let mut err_loc = vec![1];
+        let mut old_loc = vec![1];
+        let shift = synd.len() - parity;
+        for i in 0..parity {
+            let k = i + shift;
+            let mut delta = synd[k];
+            for j in 1..err_loc.len() {
+                delta ^= mul(err_loc[err_loc.len() - 1 - j], synd[k - j]);
+            }
+            old_loc.push(0);
+            if delta != 0 {
+                if old_loc.len() > err_loc.len() {
+                    let new_loc = poly_scale(&old_loc, delta);
+                    old_loc = poly_scale(&err_loc, inverse(delta));
+                    err_loc = new_loc;
+                }
+                err_loc = poly_add(&err_loc, &poly_scale(&old_loc, delta));
+            }
+        }
+        let leading = err_loc.iter().position(|c| *c != 0)?;
+        let err_loc = err_loc.split_off(leading);
+        ((err_loc.len() - 1) * 2 <= parity).then_some(err_loc)
+    }
+
+    /// Chien search over the reversed locator, positions counted from the codeword start.
+    fn error_positions(err_loc_reversed: &[u8], len: usize) -> Option<Vec<usize>> {
+        let positions: Vec<usize> = (0..len)
+            .filter(|i| poly_eval(err_loc_reversed, pow(2, *i as i32)) == 0)
+            .map(|i| len - 1 - i)
+            .collect();
+        (positions.len() == err_loc_reversed.len() - 1).then_some(positions)
+    }
+
+    /// Forney: the value to XOR in at each of `positions`.
+    fn correct(codeword: &mut [u8], synd: &[u8], positions: &[usize]) {
+        let coef_positions: Vec<usize> = positions.iter().map(|p| codeword.len() - 1 - p).collect();
+        let locator = coef_positions.iter().fold(vec![1], |loc, p| {
+            poly_mul(&loc, &poly_add(&[1], &[pow(2, *p as i32), 0]))
+        });
+        let synd_reversed: Vec<u8> = synd.iter().rev().copied().collect();
+        let mut modulus = vec![0; locator.len() + 1];
+        modulus[0] = 1;
+        let mut evaluator = poly_rem(&poly_mul(&synd_reversed, &locator), &modulus);
+        evaluator.reverse();
+        let roots: Vec<u8> = coef_positions
+            .iter()
+            .map(|p| pow(2, -(255 - *p as i32)))
+            .collect();
+        for (i, x) in roots.iter().enumerate() {
+            let x_inv = inverse(*x);
+            let locator_prime = roots
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(1, |prime, (_, xj)| mul(prime, 1 ^ mul(x_inv, *xj)));
+            let evaluator_reversed: Vec<u8> = evaluator.iter().rev().copied().collect();
+            let y = mul(*x, poly_eval(&evaluator_reversed, x_inv));
+            codeword[positions[i]] ^= div(y, locator_prime);
+        }
+    }
+
+    /// The corrected codeword, `None` if it can't be corrected.
+    pub fn decode(codeword: &[u8], parity: usize) -> Option<Vec<u8>> {
+        let mut out = codeword.to_vec();
+        let synd = syndromes(&out, parity);
+        if synd.iter().all(|s| *s == 0) {
+            return Some(out);
+        }
+        let mut err_loc = error_locator(&synd, parity)?;
+        err_loc.reverse();
+        let positions = error_positions(&err_loc, out.len())?;
+        correct(&mut out, &synd, &positions);
+        syndromes(&out, parity)
+            .iter()
+            .all(|s| *s == 0)
+            .then_some(out)
+    }
+
+    mod test {
+        #[allow(unused_imports)]
+        use super::*;
+        #[test]
+        fn test_reed_solomon() {
+            // the example from the wikiversity article
+            let data = b"hello world";
+            let codeword = encode(data, 10);
+            assert_eq!(
+                codeword[data.len()..],
+                [0xed, 0x25, 0x54, 0xc4, 0xfd, 0xfd, 0x89, 0xf3, 0xa8, 0xaa]
+            );
+            let mut corrupted = codeword.clone();
+            for i in [0, 4, 10, 15, 20] {
+                corrupted[i] ^= 0x5a;
+            }
+            assert_eq!(decode(&corrupted, 10), Some(codeword.clone()));
+            corrupted[8] ^= 1;
+            assert_eq!(decode(&corrupted, 10), None);
+        }
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let codec = StorageCodec::default();
+        let text = "o pana e sona lili tawa mi. ";
+        let bases = codec.encode_str(text);
+        assert_eq!(bases.len(), (text.len() + 16) * BASES_PER_BYTE);
+        assert!(bases.windows(2).all(|pair| pair[0] != pair[1]));
+        assert_eq!(codec.decode_string(&bases), Ok(text.to_string()));
+        assert_eq!(codec.decode(&codec.encode(&[])), Ok(Vec::new()));
+        assert_eq!(
+            codec.decode_mrna(&codec.encode_mrna(text)),
+            Ok(text.to_string())
+        );
+        // every byte value, over more than one block
+        let data: Vec<u8> = (0..=255).cycle().take(600).collect();
+        assert_eq!(codec.decode(&codec.encode(&data)), Ok(data));
+    }
+
+    #[test]
+    fn test_corrects_mutations() {
+        let codec = StorageCodec::default();
+        let mut bases = codec.encode_str("a secret in a cell");
+        // each substitution spoils at most two bytes, the next base reads against it
+        for i in [3, 40, 41, 100, 150] {
+            bases[i] = rotate(bases[i - 1], (unrotate(bases[i - 1], bases[i]) + 1) % 3);
+        }
+        assert_eq!(
+            codec.decode_string(&bases),
+            Ok("a secret in a cell".to_string())
+        );
+        // one bad digit in every byte
+        let mut ruined = bases.clone();
+        for i in (5..ruined.len()).step_by(BASES_PER_BYTE) {
+            ruined[i] = rotate(ruined[i - 1], (unrotate(ruined[i - 1], ruined[i]) + 1) % 3);
+        }
+        assert_eq!(
+            codec.decode(&ruined),
+            Err(StorageError::TooManyErrors { block: 0 })
+        );
+        assert_eq!(
+            codec.decode(&bases[1..]),
+            Err(StorageError::Length {
+                length: bases.len() - 1
+            })
+        );
+    }
+}
@@ -1,9 +1,19 @@
+use std::borrow::Cow;
+
+use serde::Deserialize;
+
 use crate::game::assets::Scene3dKey;
 
-use super::Codon;
+use super::{protein::AminoAcid, Codon};
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct NucleobaseRnaToAminoChar {
+    name: Cow<'static, str>,
+    /// `None` for tables loaded from files.
+    mode: Option<TranslationMode>,
     table: [[[char; 4]; 4]; 4],
+    /// Indexed by [`Codon::to_index`].
+    starts: [bool; 64],
 }
 impl NucleobaseRnaToAminoChar {
     pub const fn default() -> Self {
@@ -11,114 +21,444 @@ impl NucleobaseRnaToAminoChar {
     }
 }
 
+/// One message's worth of translation, from a start codon through its stop codon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    /// Codon index of the start codon.
+    pub start: usize,
+    /// Codon index one past the stop codon.
+    pub end: usize,
+    /// Start and stop included, like `"^Toki Pona."`.
+    pub text: String,
+}
+
+/// Which genetic code to translate with.
+/// Apart from `TokiPona` these are the
+/// [NCBI translation tables](https://www.ncbi.nlm.nih.gov/Taxonomy/Utils/wprintgc.cgi),
+/// each one is the standard code with a few codons reassigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub enum TranslationMode {
+    /// The standard code, NCBI table 1.
     Real,
     TokiPona,
+    VertebrateMitochondrial,
+    YeastMitochondrial,
+    MoldMitochondrial,
+    InvertebrateMitochondrial,
+    Ciliate,
+    EchinodermMitochondrial,
+    Euplotid,
+    BacterialPlastid,
+    AlternativeYeast,
+    AscidianMitochondrial,
+    AlternativeFlatwormMitochondrial,
+    ChlorophyceanMitochondrial,
+    TrematodeMitochondrial,
+    ScenedesmusMitochondrial,
+    ThraustochytriumMitochondrial,
+    RhabdopleuridaeMitochondrial,
+    Gracilibacteria,
+    Pachysolen,
+    Mesodinium,
+    Peritrich,
+    CephalodiscidaeMitochondrial,
 }
 
-impl NucleobaseRnaToAminoChar {
-    pub const fn new(mode: TranslationMode) -> Self {
-        let table = match mode {
-            TranslationMode::Real => [
-                //U
-                [
-                    // UUU - UUG
-                    ['F', 'F', 'L', 'L'],
-                    // UCU - UCG
-                    ['S', 'S', 'S', 'S'],
-                    // UAU - UAG
-                    ['Y', 'Y', '.', '.'],
-                    // UGU - UGG
-                    ['C', 'C', '.', 'W'],
-                ],
-                // C
-                [
-                    // CUU - CUG
-                    ['L', 'L', 'L', 'L'],
-                    // CCU - CCG
-                    ['P', 'P', 'P', 'P'],
-                    // CAU - CAG
-                    ['H', 'H', 'Q', 'Q'],
-                    // CGU - CGG
-                    ['R', 'R', 'R', 'R'],
-                ],
-                // A
-                [
-                    // AUU - AUG
-                    ['I', 'I', 'I', 'M'],
-                    // ACU - ACG
-                    ['T', 'T', 'T', 'T'],
-                    // AAU - AAG
-                    ['N', 'N', 'K', 'K'],
-                    // AGU - AGG
-                    ['S', 'S', 'R', 'R'],
-                ],
-                // G
-                [
-                    // GUU - GUG
-                    ['V', 'V', 'V', 'V'],
-                    // GCU - GCG
-                    ['A', 'A', 'A', 'A'],
-                    // GAU - GAG
-                    ['D', 'D', 'E', 'E'],
-                    // GGU - GGG
-                    ['G', 'G', 'G', 'G'],
-                ],
+impl TranslationMode {
+    pub const ALL: [TranslationMode; 23] = [
+        TranslationMode::Real,
+        TranslationMode::TokiPona,
+        TranslationMode::VertebrateMitochondrial,
+        TranslationMode::YeastMitochondrial,
+        TranslationMode::MoldMitochondrial,
+        TranslationMode::InvertebrateMitochondrial,
+        TranslationMode::Ciliate,
+        TranslationMode::EchinodermMitochondrial,
+        TranslationMode::Euplotid,
+        TranslationMode::BacterialPlastid,
+        TranslationMode::AlternativeYeast,
+        TranslationMode::AscidianMitochondrial,
+        TranslationMode::AlternativeFlatwormMitochondrial,
+        TranslationMode::ChlorophyceanMitochondrial,
+        TranslationMode::TrematodeMitochondrial,
+        TranslationMode::ScenedesmusMitochondrial,
+        TranslationMode::ThraustochytriumMitochondrial,
+        TranslationMode::RhabdopleuridaeMitochondrial,
+        TranslationMode::Gracilibacteria,
+        TranslationMode::Pachysolen,
+        TranslationMode::Mesodinium,
+        TranslationMode::Peritrich,
+        TranslationMode::CephalodiscidaeMitochondrial,
+    ];
+
+    /// The NCBI `transl_table` number, `None` for our own codes.
+    pub const fn ncbi_id(&self) -> Option<u8> {
+        match self {
+            TranslationMode::Real => Some(1),
+            TranslationMode::TokiPona => None,
+            TranslationMode::VertebrateMitochondrial => Some(2),
+            TranslationMode::YeastMitochondrial => Some(3),
+            TranslationMode::MoldMitochondrial => Some(4),
+            TranslationMode::InvertebrateMitochondrial => Some(5),
+            TranslationMode::Ciliate => Some(6),
+            TranslationMode::EchinodermMitochondrial => Some(9),
+            TranslationMode::Euplotid => Some(10),
+            TranslationMode::BacterialPlastid => Some(11),
+            TranslationMode::AlternativeYeast => Some(12),
+            TranslationMode::AscidianMitochondrial => Some(13),
+            TranslationMode::AlternativeFlatwormMitochondrial => Some(14),
+            TranslationMode::ChlorophyceanMitochondrial => Some(16),
+            TranslationMode::TrematodeMitochondrial => Some(21),
+            TranslationMode::ScenedesmusMitochondrial => Some(22),
+            TranslationMode::ThraustochytriumMitochondrial => Some(23),
+            TranslationMode::RhabdopleuridaeMitochondrial => Some(24),
+            TranslationMode::Gracilibacteria => Some(25),
+            TranslationMode::Pachysolen => Some(26),
+            TranslationMode::Mesodinium => Some(29),
+            TranslationMode::Peritrich => Some(30),
+            TranslationMode::CephalodiscidaeMitochondrial => Some(33),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            TranslationMode::Real => "Standard",
+            TranslationMode::TokiPona => "Toki Pona",
+            TranslationMode::VertebrateMitochondrial => "Vertebrate Mitochondrial",
+            TranslationMode::YeastMitochondrial => "Yeast Mitochondrial",
+            TranslationMode::MoldMitochondrial => "Mold, Protozoan and Coelenterate Mitochondrial",
+            TranslationMode::InvertebrateMitochondrial => "Invertebrate Mitochondrial",
+            TranslationMode::Ciliate => "Ciliate, Dasycladacean and Hexamita Nuclear",
+            TranslationMode::EchinodermMitochondrial => "Echinoderm and Flatworm Mitochondrial",
+            TranslationMode::Euplotid => "Euplotid Nuclear",
+            TranslationMode::BacterialPlastid => "Bacterial, Archaeal and Plant Plastid",
+            TranslationMode::AlternativeYeast => "Alternative Yeast Nuclear",
+            TranslationMode::AscidianMitochondrial => "Ascidian Mitochondrial",
+            TranslationMode::AlternativeFlatwormMitochondrial => {
+                "Alternative Flatworm Mitochondrial"
+            }
+            TranslationMode::ChlorophyceanMitochondrial => "Chlorophycean Mitochondrial",
+            TranslationMode::TrematodeMitochondrial => "Trematode Mitochondrial",
+            TranslationMode::ScenedesmusMitochondrial => "Scenedesmus obliquus Mitochondrial",
+            TranslationMode::ThraustochytriumMitochondrial => "Thraustochytrium Mitochondrial",
+            TranslationMode::RhabdopleuridaeMitochondrial => "Rhabdopleuridae Mitochondrial",
+            TranslationMode::Gracilibacteria => "Candidate Division SR1 and Gracilibacteria",
+            TranslationMode::Pachysolen => "Pachysolen tannophilus Nuclear",
+            TranslationMode::Mesodinium => "Mesodinium Nuclear",
+            TranslationMode::Peritrich => "Peritrich Nuclear",
+            TranslationMode::CephalodiscidaeMitochondrial => "Cephalodiscidae Mitochondrial",
+        }
+    }
+
+    /// Codons whose meaning differs from the standard code, `.` is a stop.
+    const fn reassignments(&self) -> &'static [(&'static [u8; 3], char)] {
+        match self {
+            TranslationMode::Real
+            | TranslationMode::TokiPona
+            | TranslationMode::BacterialPlastid => &[],
+            TranslationMode::VertebrateMitochondrial => {
+                &[(b"AGA", '.'), (b"AGG", '.'), (b"AUA", 'M'), (b"UGA", 'W')]
+            }
+            TranslationMode::YeastMitochondrial => &[
+                (b"AUA", 'M'),
+                (b"CUU", 'T'),
+                (b"CUC", 'T'),
+                (b"CUA", 'T'),
+                (b"CUG", 'T'),
+                (b"UGA", 'W'),
+            ],
+            TranslationMode::MoldMitochondrial => &[(b"UGA", 'W')],
+            TranslationMode::InvertebrateMitochondrial => {
+                &[(b"AGA", 'S'), (b"AGG", 'S'), (b"AUA", 'M'), (b"UGA", 'W')]
+            }
+            TranslationMode::Ciliate => &[(b"UAA", 'Q'), (b"UAG", 'Q')],
+            TranslationMode::EchinodermMitochondrial => {
+                &[(b"AAA", 'N'), (b"AGA", 'S'), (b"AGG", 'S'), (b"UGA", 'W')]
+            }
+            TranslationMode::Euplotid => &[(b"UGA", 'C')],
+            TranslationMode::AlternativeYeast => &[(b"CUG", 'S')],
+            TranslationMode::AscidianMitochondrial => {
+                &[(b"AGA", 'G'), (b"AGG", 'G'), (b"AUA", 'M'), (b"UGA", 'W')]
+            }
+            TranslationMode::AlternativeFlatwormMitochondrial => &[
+                (b"AAA", 'N'),
+                (b"AGA", 'S'),
+                (b"AGG", 'S'),
+                (b"UAA", 'Y'),
+                (b"UGA", 'W'),
             ],
-            TranslationMode::TokiPona => [
-                //U
-                [
-                    // UUU - UUG
-                    ['U', 'u', 'L', 'l'],
-                    // UCU - UCG
-                    ['S', 's', 's', 's'],
-                    // UAU - UAG
-                    ['J', 'j', '.', '.'],
-                    // UGU - UGG
-                    ['s', 's', '.', 'W'],
-                ],
-                // C
-                [
-                    // CUU - CUG
-                    ['l', 'l', 'l', 'l'],
-                    // CCU - CCG
-                    ['P', 'p', 'p', 'p'],
-                    // CAU - CAG
-                    ['u', 'u', 'O', 'o'],
-                    // CGU - CGG
-                    ['w', 'K', 'k', 'k'],
-                ],
-                // A
-                [
-                    // AUU - AUG
-                    ['I', 'i', 'i', '^'],
-                    // ACU - ACG
-                    ['T', 't', 't', 't'],
-                    // AAU - AAG
-                    ['N', 'n', 'E', 'e'],
-                    // AGU - AGG
-                    ['s', 's', 'M', 'm'],
-                ],
-                // G
-                [
-                    // GUU - GUG
-                    [' ', '0', '1', '2'],
-                    // GCU - GCG
-                    ['3', 'A', 'a', 'a'],
-                    // GAU - GAG
-                    ['+', '-', '*', '/'],
-                    // GGU - GGG
-                    ['_', ',', '?', '!'],
-                ],
+            TranslationMode::ChlorophyceanMitochondrial => &[(b"UAG", 'L')],
+            TranslationMode::TrematodeMitochondrial => &[
+                (b"UGA", 'W'),
+                (b"AUA", 'M'),
+                (b"AGA", 'S'),
+                (b"AGG", 'S'),
+                (b"AAA", 'N'),
             ],
+            TranslationMode::ScenedesmusMitochondrial => &[(b"UCA", '.'), (b"UAG", 'L')],
+            TranslationMode::ThraustochytriumMitochondrial => &[(b"UUA", '.')],
+            TranslationMode::RhabdopleuridaeMitochondrial => {
+                &[(b"AGA", 'S'), (b"AGG", 'K'), (b"UGA", 'W')]
+            }
+            TranslationMode::Gracilibacteria => &[(b"UGA", 'G')],
+            TranslationMode::Pachysolen => &[(b"CUG", 'A')],
+            TranslationMode::Mesodinium => &[(b"UAA", 'Y'), (b"UAG", 'Y')],
+            TranslationMode::Peritrich => &[(b"UAA", 'E'), (b"UAG", 'E')],
+            TranslationMode::CephalodiscidaeMitochondrial => {
+                &[(b"UAA", 'Y'), (b"UGA", 'W'), (b"AGA", 'S'), (b"AGG", 'K')]
+            }
+        }
+    }
+
+    /// Codons a ribosome may initiate on.
+    const fn starts(&self) -> &'static [&'static [u8; 3]] {
+        match self {
+            TranslationMode::Real => &[b"UUG", b"CUG", b"AUG"],
+            TranslationMode::TokiPona
+            | TranslationMode::Ciliate
+            | TranslationMode::Euplotid
+            | TranslationMode::AlternativeFlatwormMitochondrial
+            | TranslationMode::ChlorophyceanMitochondrial
+            | TranslationMode::ScenedesmusMitochondrial
+            | TranslationMode::Mesodinium
+            | TranslationMode::Peritrich => &[b"AUG"],
+            TranslationMode::VertebrateMitochondrial => &[b"AUU", b"AUC", b"AUA", b"AUG", b"GUG"],
+            TranslationMode::YeastMitochondrial => &[b"AUA", b"AUG", b"GUG"],
+            TranslationMode::MoldMitochondrial => &[
+                b"UUA", b"UUG", b"CUG", b"AUU", b"AUC", b"AUA", b"AUG", b"GUG",
+            ],
+            TranslationMode::InvertebrateMitochondrial => {
+                &[b"UUG", b"AUU", b"AUC", b"AUA", b"AUG", b"GUG"]
+            }
+            TranslationMode::EchinodermMitochondrial | TranslationMode::TrematodeMitochondrial => {
+                &[b"AUG", b"GUG"]
+            }
+            TranslationMode::BacterialPlastid => {
+                &[b"UUG", b"CUG", b"AUU", b"AUC", b"AUA", b"AUG", b"GUG"]
+            }
+            TranslationMode::AlternativeYeast | TranslationMode::Pachysolen => &[b"CUG", b"AUG"],
+            TranslationMode::AscidianMitochondrial => &[b"UUG", b"AUA", b"AUG", b"GUG"],
+            TranslationMode::ThraustochytriumMitochondrial => &[b"AUU", b"AUG", b"GUG"],
+            TranslationMode::RhabdopleuridaeMitochondrial
+            | TranslationMode::CephalodiscidaeMitochondrial => &[b"UUG", b"CUG", b"AUG", b"GUG"],
+            TranslationMode::Gracilibacteria => &[b"UUG", b"AUG", b"GUG"],
+        }
+    }
+}
+
+const fn base_index(b: u8) -> usize {
+    match b {
+        b'U' => 0,
+        b'C' => 1,
+        b'A' => 2,
+        b'G' => 3,
+        _ => panic!("not a nucleobase"),
+    }
+}
+
+const STANDARD_TABLE: [[[char; 4]; 4]; 4] = [
+    //U
+    [
+        // UUU - UUG
+        ['F', 'F', 'L', 'L'],
+        // UCU - UCG
+        ['S', 'S', 'S', 'S'],
+        // UAU - UAG
+        ['Y', 'Y', '.', '.'],
+        // UGU - UGG
+        ['C', 'C', '.', 'W'],
+    ],
+    // C
+    [
+        // CUU - CUG
+        ['L', 'L', 'L', 'L'],
+        // CCU - CCG
+        ['P', 'P', 'P', 'P'],
+        // CAU - CAG
+        ['H', 'H', 'Q', 'Q'],
+        // CGU - CGG
+        ['R', 'R', 'R', 'R'],
+    ],
+    // A
+    [
+        // AUU - AUG
+        ['I', 'I', 'I', 'M'],
+        // ACU - ACG
+        ['T', 'T', 'T', 'T'],
+        // AAU - AAG
+        ['N', 'N', 'K', 'K'],
+        // AGU - AGG
+        ['S', 'S', 'R', 'R'],
+    ],
+    // G
+    [
+        // GUU - GUG
+        ['V', 'V', 'V', 'V'],
+        // GCU - GCG
+        ['A', 'A', 'A', 'A'],
+        // GAU - GAG
+        ['D', 'D', 'E', 'E'],
+        // GGU - GGG
+        ['G', 'G', 'G', 'G'],
+    ],
+];
+
+const TOKI_PONA_TABLE: [[[char; 4]; 4]; 4] = [
+    //U
+    [
+        // UUU - UUG
+        ['U', 'u', 'L', 'l'],
+        // UCU - UCG
+        ['S', 's', 's', 's'],
+        // UAU - UAG
+        ['J', 'j', '.', '.'],
+        // UGU - UGG
+        ['s', 's', '.', 'W'],
+    ],
+    // C
+    [
+        // CUU - CUG
+        ['l', 'l', 'l', 'l'],
+        // CCU - CCG
+        ['P', 'p', 'p', 'p'],
+        // CAU - CAG
+        ['u', 'u', 'O', 'o'],
+        // CGU - CGG
+        ['w', 'K', 'k', 'k'],
+    ],
+    // A
+    [
+        // AUU - AUG
+        ['I', 'i', 'i', '^'],
+        // ACU - ACG
+        ['T', 't', 't', 't'],
+        // AAU - AAG
+        ['N', 'n', 'E', 'e'],
+        // AGU - AGG
+        ['s', 's', 'M', 'm'],
+    ],
+    // G
+    [
+        // GUU - GUG
+        [' ', '0', '1', '2'],
+        // GCU - GCG
+        ['3', 'A', 'a', 'a'],
+        // GAU - GAG
+        ['+', '-', '*', '/'],
+        // GGU - GGG
+        ['_', ',', '?', '!'],
+    ],
+];
+
+impl NucleobaseRnaToAminoChar {
+    pub const fn new(mode: TranslationMode) -> Self {
+        let mut table = match mode {
+            TranslationMode::TokiPona => TOKI_PONA_TABLE,
+            _ => STANDARD_TABLE,
         };
-        Self { table }
+        let reassignments = mode.reassignments();
+        let mut i = 0;
+        while i < reassignments.len() {
+            let (codon, c) = reassignments[i];
+            table[base_index(codon[0])][base_index(codon[1])][base_index(codon[2])] = c;
+            i += 1;
+        }
+        let mut starts = [false; 64];
+        let mode_starts = mode.starts();
+        let mut i = 0;
+        while i < mode_starts.len() {
+            let codon = mode_starts[i];
+            starts[base_index(codon[0]) * 16 + base_index(codon[1]) * 4 + base_index(codon[2])] =
+                true;
+            i += 1;
+        }
+        Self {
+            name: Cow::Borrowed(mode.name()),
+            mode: Some(mode),
+            table,
+            starts,
+        }
+    }
+    /// A table of our own, `translations` in chart order (see [`Codon::from_index`]).
+    pub fn custom(name: impl Into<String>, translations: [char; 64], starts: &[Codon]) -> Self {
+        let mut table = [[[' '; 4]; 4]; 4];
+        for (i, c) in translations.into_iter().enumerate() {
+            let codon = Codon::from_index(i);
+            table[codon.i0()][codon.i1()][codon.i2()] = c;
+        }
+        let mut start_flags = [false; 64];
+        for codon in starts {
+            start_flags[codon.to_index()] = true;
+        }
+        Self {
+            name: Cow::Owned(name.into()),
+            mode: None,
+            table,
+            starts: start_flags,
+        }
+    }
+    /// The built in code this table came from, `None` for tables loaded from files.
+    pub fn mode(&self) -> Option<TranslationMode> {
+        self.mode
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn is_start(&self, codon: &Codon) -> bool {
+        self.starts[codon.to_index()]
+    }
+    pub fn is_stop(&self, codon: &Codon) -> bool {
+        self.translation(codon) == '.'
+    }
+    /// Every codon reads as a one letter amino acid code or a stop, like the NCBI codes
+    /// and files written in them, not a cipher like Toki Pona.
+    pub fn is_amino_acid(&self) -> bool {
+        (0..64).map(Codon::from_index).all(|codon| {
+            self.is_stop(&codon) || AminoAcid::from_char(self.translation(&codon)).is_some()
+        })
+    }
+    /// Read `codons` like a ribosome: scan for a start codon, translate through the next stop,
+    /// release that product and scan on from after it. A start without a stop yields nothing.
+    pub fn products(&self, codons: &[Codon]) -> Vec<Product> {
+        let mut products = Vec::new();
+        let mut open: Option<usize> = None;
+        for (i, codon) in codons.iter().enumerate() {
+            match open {
+                None if self.is_start(codon) => open = Some(i),
+                Some(start) if self.is_stop(codon) => {
+                    products.push(Product {
+                        start,
+                        end: i + 1,
+                        text: codons[start..=i]
+                            .iter()
+                            .map(|c| self.translation(c))
+                            .collect(),
+                    });
+                    open = None;
+                }
+                _ => {}
+            }
+        }
+        products
+    }
+    pub fn start_codons(&self) -> Vec<Codon> {
+        (0..64)
+            .map(Codon::from_index)
+            .filter(|codon| self.is_start(codon))
+            .collect()
+    }
+    pub fn stop_codons(&self) -> Vec<Codon> {
+        (0..64)
+            .map(Codon::from_index)
+            .filter(|codon| self.is_stop(codon))
+            .collect()
     }
     pub fn translation(&self, codon: &Codon) -> char {
         self.table[codon.i0()][codon.i1()][codon.i2()]
     }
-    pub fn scene_3d_key(&self, c: char) -> Scene3dKey {
-        match c {
+    /// The 3d glyph for a translated character, `None` if there is no model for it.
+    pub fn scene_3d_key(&self, c: char) -> Option<Scene3dKey> {
+        let key = match c {
             '-' => Scene3dKey::Minus,
             '_' => Scene3dKey::Underscore,
             '^' => Scene3dKey::Carrot,
@@ -164,14 +504,17 @@ impl NucleobaseRnaToAminoChar {
             'W' => Scene3dKey::W,
             'w' => Scene3dKey::WLower,
 
-            _ => panic!("unexpected translation {}", c),
-        }
+            _ => return None,
+        };
+        Some(key)
     }
 }
 
 mod test {
     #[allow(unused_imports)]
     use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::Mrna;
     struct CodonAcids {
         codon: Codon,
         expected_real: char,
@@ -276,4 +619,80 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_products() {
+        let codons = |s: &str| Mrna::new(s).codons().to_vec();
+        let table = NucleobaseRnaToAminoChar::default();
+        // junk before the start, two messages, junk between and an unfinished third
+        let products = table.products(&codons("ACUAUGACUUAAACUUAAAUGCAGUAGAUGACU"));
+        assert_eq!(
+            products,
+            vec![
+                Product {
+                    start: 1,
+                    end: 4,
+                    text: "^T.".to_string()
+                },
+                Product {
+                    start: 6,
+                    end: 9,
+                    text: "^o.".to_string()
+                },
+            ]
+        );
+        // a start inside a product is read as a normal codon
+        assert_eq!(table.products(&codons("AUGAUGUAA"))[0].text, "^^.");
+        assert!(table.products(&codons("UAAAUG")).is_empty());
+    }
+
+    #[test]
+    fn test_alternative_codes() {
+        let standard = NucleobaseRnaToAminoChar::new(TranslationMode::Real);
+        let mito = NucleobaseRnaToAminoChar::new(TranslationMode::VertebrateMitochondrial);
+        assert_eq!(standard.translation(&Codon::new('U', 'G', 'A')), '.');
+        assert_eq!(mito.translation(&Codon::new('U', 'G', 'A')), 'W');
+        assert_eq!(mito.translation(&Codon::new('A', 'U', 'A')), 'M');
+        assert!(mito.is_stop(&Codon::new('A', 'G', 'G')));
+        assert!(mito.is_start(&Codon::new('G', 'U', 'G')));
+        assert!(!standard.is_start(&Codon::new('G', 'U', 'G')));
+        let stops: Vec<String> = mito.stop_codons().iter().map(|c| c.to_string()).collect();
+        assert_eq!(stops, ["UAA", "UAG", "AGA", "AGG"]);
+
+        let ciliate = NucleobaseRnaToAminoChar::new(TranslationMode::Ciliate);
+        assert_eq!(ciliate.stop_codons(), [Codon::new('U', 'G', 'A')]);
+    }
+    #[test]
+    fn test_every_code_has_starts_and_stops() {
+        for mode in TranslationMode::ALL {
+            let table = NucleobaseRnaToAminoChar::new(mode);
+            assert!(!table.start_codons().is_empty(), "{}", mode.name());
+            assert!(!table.stop_codons().is_empty(), "{}", mode.name());
+            assert_eq!(table.mode(), Some(mode));
+        }
+    }
+    #[test]
+    fn test_is_amino_acid() {
+        for mode in TranslationMode::ALL {
+            let table = NucleobaseRnaToAminoChar::new(mode);
+            assert_eq!(
+                table.is_amino_acid(),
+                mode != TranslationMode::TokiPona,
+                "{}",
+                mode.name()
+            );
+        }
+        // a file table has no mode, its contents decide
+        let mut translations = [' '; 64];
+        for (i, c) in translations.iter_mut().enumerate() {
+            *c = NucleobaseRnaToAminoChar::new(TranslationMode::Real)
+                .translation(&Codon::from_index(i));
+        }
+        let file = NucleobaseRnaToAminoChar::custom(
+            "Standard",
+            translations,
+            &[Codon::new('A', 'U', 'G')],
+        );
+        assert!(file.is_amino_acid());
+    }
 }
@@ -0,0 +1,270 @@
+//! Transfer RNAs: an anticodon that pairs with codons, carrying one character of the code.
+//! The first (5') anticodon base pairs loosely with the third codon base, following
+//! [Crick's wobble rules](https://en.wikipedia.org/wiki/Wobble_base_pair),
+//! so one tRNA can decode several codons.
+
+use super::{translation::NucleobaseRnaToAminoChar, Codon, NucleobaseRna};
+
+/// A base at the wobble position, which may be the modified base inosine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WobbleBase {
+    Adenine,
+    Uracil,
+    Cytosine,
+    Guanine,
+    Inosine,
+}
+
+impl WobbleBase {
+    pub const ALL: [WobbleBase; 5] = [
+        WobbleBase::Cytosine,
+        WobbleBase::Adenine,
+        WobbleBase::Guanine,
+        WobbleBase::Uracil,
+        WobbleBase::Inosine,
+    ];
+
+    pub fn to_char(&self) -> char {
+        match self {
+            WobbleBase::Adenine => 'A',
+            WobbleBase::Uracil => 'U',
+            WobbleBase::Cytosine => 'C',
+            WobbleBase::Guanine => 'G',
+            WobbleBase::Inosine => 'I',
+        }
+    }
+
+    /// The third codon bases this pairs with: C·G, A·U, G·U and G·C, U·A and U·G, I·U I·C and I·A.
+    pub fn pairs_with(&self, base: NucleobaseRna) -> bool {
+        use NucleobaseRna::*;
+        match self {
+            WobbleBase::Cytosine => base == Guanine,
+            WobbleBase::Adenine => base == Uracil,
+            WobbleBase::Guanine => matches!(base, Uracil | Cytosine),
+            WobbleBase::Uracil => matches!(base, Adenine | Guanine),
+            WobbleBase::Inosine => matches!(base, Uracil | Cytosine | Adenine),
+        }
+    }
+
+    fn watson_crick(base: NucleobaseRna) -> Self {
+        match base {
+            NucleobaseRna::Adenine => WobbleBase::Uracil,
+            NucleobaseRna::Uracil => WobbleBase::Adenine,
+            NucleobaseRna::Cytosine => WobbleBase::Guanine,
+            NucleobaseRna::Guanine => WobbleBase::Cytosine,
+        }
+    }
+}
+
+/// Read 5' to 3', so `bases.0` is the wobble base and `bases.2` pairs with the first codon base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Anticodon {
+    pub bases: (WobbleBase, NucleobaseRna, NucleobaseRna),
+}
+
+impl Anticodon {
+    /// The anticodon that pairs with `codon` by strict Watson-Crick pairing,
+    /// the same bases as [`Codon::anticodon`].
+    pub fn pairing(codon: &Codon) -> Self {
+        Anticodon {
+            bases: (
+                WobbleBase::watson_crick(codon.bases.2),
+                codon.bases.1.complement(),
+                codon.bases.0.complement(),
+            ),
+        }
+    }
+
+    pub fn decodes(&self, codon: &Codon) -> bool {
+        self.bases.2.complement() == codon.bases.0
+            && self.bases.1.complement() == codon.bases.1
+            && self.bases.0.pairs_with(codon.bases.2)
+    }
+
+    /// Every codon this anticodon reads, in chart order.
+    pub fn decoded_codons(&self) -> Vec<Codon> {
+        (0..64)
+            .map(Codon::from_index)
+            .filter(|codon| self.decodes(codon))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Anticodon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.bases.0.to_char(),
+            self.bases.1.to_char(),
+            self.bases.2.to_char()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferRna {
+    pub anticodon: Anticodon,
+    /// The character of the code it carries, an amino acid for the real codes.
+    pub amino: char,
+}
+
+impl TransferRna {
+    /// Pairs with `codon` and carries what `table` translates it to.
+    pub fn is_correct_for(&self, codon: &Codon, table: &NucleobaseRnaToAminoChar) -> bool {
+        self.anticodon.decodes(codon) && self.amino == table.translation(codon)
+    }
+}
+
+/// The tRNAs of `trnas` whose anticodon pairs with `codon`, whatever they carry.
+pub fn decoding_trnas<'a>(trnas: &'a [TransferRna], codon: &Codon) -> Vec<&'a TransferRna> {
+    trnas
+        .iter()
+        .filter(|trna| trna.anticodon.decodes(codon))
+        .collect()
+}
+
+/// As few tRNAs as possible that decode every sense codon of `table` with the right character.
+/// A tRNA is only allowed to also pair with a codon meaning something else (or a stop)
+/// where the code splits a wobble pair, like Toki Pona's `UUU` U and `UUC` u.
+/// The set is in chart order.
+pub fn minimal_trna_set(table: &NucleobaseRnaToAminoChar) -> Vec<TransferRna> {
+    let mut set = Vec::new();
+    // codons sharing the first two bases are a box, only the third base wobbles
+    for first_two in 0..16 {
+        let codon = |third: usize| Codon::from_index(first_two * 4 + third);
+        let mut characters: Vec<char> = Vec::new();
+        for c in (0..4).map(codon).filter(|c| !table.is_stop(c)) {
+            if !characters.contains(&table.translation(&c)) {
+                characters.push(table.translation(&c));
+            }
+        }
+        for amino in characters {
+            set.extend(
+                cover_box(table, &(0..4).map(codon).collect::<Vec<_>>(), amino)
+                    .into_iter()
+                    .map(|anticodon| TransferRna { anticodon, amino }),
+            );
+        }
+    }
+    set
+}
+
+/// The fewest anticodons covering the codons for `amino` in a box of four codons,
+/// preferring sets that misread fewer codons of the box.
+fn cover_box(table: &NucleobaseRnaToAminoChar, codons: &[Codon], amino: char) -> Vec<Anticodon> {
+    let wanted: Vec<&Codon> = codons
+        .iter()
+        .filter(|c| !table.is_stop(c) && table.translation(c) == amino)
+        .collect();
+    let first = codons[0];
+    let candidates: Vec<Anticodon> = WobbleBase::ALL
+        .iter()
+        .map(|wobble| Anticodon {
+            bases: (
+                *wobble,
+                first.bases.1.complement(),
+                first.bases.0.complement(),
+            ),
+        })
+        .filter(|anticodon| wanted.iter().any(|c| anticodon.decodes(c)))
+        .collect();
+    let mut best: Option<(usize, usize, Vec<Anticodon>)> = None;
+    for mask in 1u32..(1 << candidates.len()) {
+        let chosen: Vec<Anticodon> = (0..candidates.len())
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| candidates[i])
+            .collect();
+        let covers = wanted
+            .iter()
+            .all(|c| chosen.iter().any(|anticodon| anticodon.decodes(c)));
+        if !covers {
+            continue;
+        }
+        let misread = codons
+            .iter()
+            .filter(|c| !wanted.contains(c))
+            .filter(|c| chosen.iter().any(|anticodon| anticodon.decodes(c)))
+            .count();
+        let score = (misread, chosen.len());
+        if best.as_ref().map_or(true, |(m, n, _)| score < (*m, *n)) {
+            best = Some((misread, chosen.len(), chosen));
+        }
+    }
+    best.map(|(_, _, chosen)| chosen).unwrap_or_default()
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::game::biology::translation::TranslationMode;
+
+    #[test]
+    fn test_wobble() {
+        let inosine = Anticodon {
+            bases: (
+                WobbleBase::Inosine,
+                NucleobaseRna::Guanine,
+                NucleobaseRna::Cytosine,
+            ),
+        };
+        assert_eq!(inosine.to_string(), "IGC");
+        let decoded: Vec<String> = inosine
+            .decoded_codons()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(decoded, ["GCU", "GCC", "GCA"]);
+
+        let g_u = Anticodon::pairing(&Codon::new('U', 'U', 'C'));
+        assert_eq!(g_u.to_string(), "GAA");
+        assert!(g_u.decodes(&Codon::new('U', 'U', 'U')));
+        assert!(!g_u.decodes(&Codon::new('U', 'U', 'A')));
+        assert_eq!(
+            Anticodon::pairing(&Codon::new('A', 'U', 'G')).to_string(),
+            Codon::new('A', 'U', 'G').anticodon().to_string()
+        );
+    }
+
+    #[test]
+    fn test_decoding_trnas() {
+        let table = NucleobaseRnaToAminoChar::new(TranslationMode::Real);
+        let trnas = minimal_trna_set(&table);
+        let codon = Codon::new('G', 'C', 'A');
+        let decoders = decoding_trnas(&trnas, &codon);
+        assert_eq!(decoders.len(), 1);
+        assert_eq!(decoders[0].amino, 'A');
+        assert!(decoders[0].is_correct_for(&codon, &table));
+        assert!(decoding_trnas(&trnas, &Codon::new('U', 'A', 'A')).is_empty());
+    }
+
+    #[test]
+    fn test_minimal_set_covers_code() {
+        for mode in [TranslationMode::Real, TranslationMode::TokiPona] {
+            let table = NucleobaseRnaToAminoChar::new(mode);
+            let trnas = minimal_trna_set(&table);
+            for codon in (0..64).map(Codon::from_index) {
+                if table.is_stop(&codon) {
+                    continue;
+                }
+                assert!(
+                    trnas.iter().any(|t| t.is_correct_for(&codon, &table)),
+                    "{mode:?} {codon}"
+                );
+            }
+        }
+        // 8 four codon boxes need 2 each, 12 two codon boxes 1, then Ile, Met and Trp
+        let standard = minimal_trna_set(&NucleobaseRnaToAminoChar::new(TranslationMode::Real));
+        assert_eq!(standard.len(), 31);
+        // the standard set never reads a codon as something else
+        for trna in &standard {
+            for codon in trna.anticodon.decoded_codons() {
+                assert!(trna.is_correct_for(
+                    &codon,
+                    &NucleobaseRnaToAminoChar::new(TranslationMode::Real)
+                ));
+            }
+        }
+    }
+}
@@ -4,26 +4,46 @@ mod cell;
 pub mod text;
 use cell::{draw_cell, CellBundle, EyeBundle};
 
+use crate::{
+    game::biology::{Mrna, MrnaBundle, SelectedMrna},
+    screen::Screen,
+};
+
 pub(super) fn plugin(app: &mut App) {
     //app.init_resource::<MyAssetPack>();
-    app.add_systems(Startup, spawn_cells);
+    app.observe(spawn_cells);
+    app.observe(text::spawn_acid);
     app.add_systems(Update, draw_cell);
 }
 
+#[derive(Event, Debug)]
+pub struct SpawnCells {
+    pub positions: Vec<Vec2>,
+    /// What the first cell's mRNA starts with.
+    pub start: Mrna,
+}
+
 fn spawn_cells(
+    trigger: Trigger<SpawnCells>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let x = 8.;
-    commands
-        .spawn(CellBundle::new(Transform::from_xyz(x, 0., 0.)))
-        .with_children(|parent| {
-            parent.spawn(EyeBundle::new(&mut meshes, &mut materials));
-        });
-    commands
-        .spawn(CellBundle::new(Transform::from_xyz(-x, 0., 0.)))
-        .with_children(|parent| {
-            parent.spawn(EyeBundle::new(&mut meshes, &mut materials));
-        });
+    let event = trigger.event();
+    // each cell transcribes its own mRNA, the first one starts out selected
+    for (i, position) in event.positions.iter().enumerate() {
+        commands
+            .spawn((
+                CellBundle::new(Transform::from_translation(position.extend(0.))),
+                StateScoped(Screen::Playing),
+            ))
+            .with_children(|parent| {
+                parent.spawn(EyeBundle::new(&mut meshes, &mut materials));
+                if i == 0 {
+                    parent.spawn((MrnaBundle::with_mrna(event.start.clone()), SelectedMrna));
+                } else {
+                    parent.spawn(MrnaBundle::default());
+                }
+            });
+    }
 }
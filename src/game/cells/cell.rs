@@ -1,11 +1,13 @@
 use bevy::{
-    color::palettes::css::{ORANGE, WHITE},
+    color::palettes::css::{ORANGE, WHITE, YELLOW},
     math::vec3,
     prelude::*,
     render::view::visibility::RenderLayers,
 };
 use noise::{NoiseFn, OpenSimplex};
 
+use crate::game::biology::SelectedMrna;
+
 #[derive(Component)]
 pub struct Curve {
     curve: CubicBezier<Vec3>,
@@ -125,6 +127,7 @@ pub fn draw_cell(
     time: Res<Time>,
     mut eye_q: Query<(&Parent, &mut Transform), With<Eye>>,
     mut cell_q: Query<(&GlobalTransform, &Curve)>,
+    selected_q: Query<&Parent, With<SelectedMrna>>,
     mut gizmos: Gizmos,
 ) {
     let t = (time.elapsed_seconds().sin() + 1.0) / 2.0;
@@ -132,8 +135,13 @@ pub fn draw_cell(
         match cell_q.get_mut(parent.get()) {
             Ok((global_transform, curve)) => {
                 let mut curve = curve.get_noised_curve(&time, &global_transform);
-                // draw the curve
-                gizmos.linestrip(curve.to_curve().iter_positions(50), WHITE);
+                // draw the curve, highlighting the cell whose mRNA is being edited
+                let color = if selected_q.iter().any(|p| p.get() == parent.get()) {
+                    YELLOW
+                } else {
+                    WHITE
+                };
+                gizmos.linestrip(curve.to_curve().iter_positions(50), color);
                 // un transform the curve to the local space of the eye
 
                 transform_curve(
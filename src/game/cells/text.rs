@@ -1,13 +1,49 @@
-use crate::game::{
-    assets::{HandleMap, Scene3dKey},
-    biology::{Codon, LogTextRes, Mrna, CODON_TABLE},
-    spawn::codon_chart::SpawnCodonChart,
+use crate::{
+    game::{
+        assets::{HandleMap, Scene3dKey},
+        biology::{
+            codon_table::ActiveCodonTable, protein::Polypeptide, ribosome::SpawnRibosome,
+            translation::NucleobaseRnaToAminoChar, Codon, LogTextRes, Mrna, ProductReleased,
+        },
+        spawn::codon_chart::SpawnCodonChart,
+    },
+    screen::Screen,
 };
 use bevy::prelude::*;
+
+/// One of the characters around the codon chart, in the active code.
+#[derive(Component, Debug)]
+pub struct CodonGlyph;
+
 pub fn spawn_codon_gltfs(
     _trigger: Trigger<SpawnCodonChart>,
     mut commands: Commands,
     scene_handles: Res<HandleMap<Scene3dKey>>,
+    active_table: Res<ActiveCodonTable>,
+) {
+    spawn_glyphs(&mut commands, &scene_handles, &active_table.table);
+}
+
+/// Swap the glyphs around the chart for the new code when F2 or a level changes it.
+pub fn respawn_codon_gltfs(
+    mut commands: Commands,
+    scene_handles: Res<HandleMap<Scene3dKey>>,
+    active_table: Res<ActiveCodonTable>,
+    glyph_q: Query<Entity, With<CodonGlyph>>,
+) {
+    if !active_table.is_changed() || glyph_q.is_empty() {
+        return;
+    }
+    for entity in &glyph_q {
+        commands.entity(entity).despawn_recursive();
+    }
+    spawn_glyphs(&mut commands, &scene_handles, &active_table.table);
+}
+
+fn spawn_glyphs(
+    commands: &mut Commands,
+    scene_handles: &HandleMap<Scene3dKey>,
+    table: &NucleobaseRnaToAminoChar,
 ) {
     // note that we have to include the `Scene0` label
     let radius = 15.;
@@ -22,11 +58,14 @@ pub fn spawn_codon_gltfs(
     for i in 0..64 {
         translation = rotate_by_angle_rad(&mut translation, angle_rad);
 
-        let c = CODON_TABLE.translation(&Codon::from_index(i));
+        let c = table.translation(&Codon::from_index(i));
         if c == ' ' {
             continue;
         }
-        let scene_3d_key = CODON_TABLE.scene_3d_key(c);
+        let Some(scene_3d_key) = table.scene_3d_key(c) else {
+            warn!("no 3d glyph for {:?}", c);
+            continue;
+        };
         info!("{} {:?}", c, scene_3d_key);
         commands.spawn((
             SceneBundle {
@@ -35,13 +74,42 @@ pub fn spawn_codon_gltfs(
                 ..Default::default()
             },
             Name::new(c.to_string()),
+            CodonGlyph,
+            StateScoped(Screen::Playing),
         ));
     }
 }
 
-pub fn spawn_acid(commands: &Commands, mrna: &Mrna, log_text: &mut LogTextRes) {
-    info!("spawn_acid {:?}", mrna);
-    log_text.text = format!("{}\n{:?}", log_text.text, mrna);
+/// Sends a ribosome down the message for each released product, and logs it,
+/// with its chemistry when the code reads as amino acids.
+pub fn spawn_acid(
+    trigger: Trigger<ProductReleased>,
+    mut commands: Commands,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    let event = trigger.event();
+    let product = &event.product;
+    info!("spawn_acid {:?} from {}", product, event.entity);
+    commands.trigger(SpawnRibosome {
+        mrna: event.mrna.clone(),
+        table: event.table.clone(),
+        from: product.start,
+    });
+    log_text.text = format!("{}\n{}", log_text.text, product.text);
+    if !event.table.is_amino_acid() {
+        return;
+    }
+    let message = Mrna::from_bases(&event.mrna.bases()[product.start * 3..product.end * 3]);
+    if let Some(chain) = Polypeptide::translate_with(&message, &event.table) {
+        log_text.text = format!(
+            "{}\n{} {:.1} Da, pI {:.2}, GRAVY {:.2}",
+            log_text.text,
+            chain,
+            chain.mass(),
+            chain.isoelectric_point(),
+            chain.gravy()
+        );
+    }
 }
 
 fn rotate_by_angle_rad(vec: &mut Vec3, angle_rad: f32) -> Vec3 {
@@ -0,0 +1,629 @@
+//! Puzzle mode: each level asks for an mRNA that translates to a Toki Pona phrase,
+//! within limits like a maximum length or banned codons.
+//! The selected mRNA is judged as it grows, once its stop codon arrives the level ends
+//! on the result screen. Levels are files, see [`level`].
+
+pub mod level;
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, LoadState},
+    prelude::*,
+};
+use level::{Level, LevelLoader, LevelPack, LevelPackLoader};
+
+use super::{
+    assets::{HandleMap, LevelPackKey},
+    biology::{
+        alignment::{align, Alignment, AlignmentMode, Scoring},
+        codon_table::ActiveCodonTable,
+        translation::Product,
+        AllowedBases, Codon, LogTextRes, Mrna, NucleobaseRna, SelectedMrna,
+    },
+};
+use crate::{screen::Screen, AppSet};
+
+/// Points for each character of the target the translation gets right.
+pub const POINTS_PER_CHARACTER: u32 = 100;
+/// Taken off for each codon beyond the length of the target.
+pub const POINTS_PER_EXTRA_CODON: u32 = 20;
+/// Taken off for each second spent on the level.
+pub const POINTS_PER_SECOND: f32 = 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Level>();
+    app.init_asset_loader::<LevelLoader>();
+    app.init_asset::<LevelPack>();
+    app.init_asset_loader::<LevelPackLoader>();
+    app.init_resource::<Levels>();
+    app.init_resource::<Puzzle>();
+    app.add_systems(Update, sync_levels);
+    app.add_systems(
+        OnEnter(Screen::Playing),
+        (start_puzzle, spawn_puzzle_hud).chain(),
+    );
+    app.add_systems(
+        Update,
+        (show_hint, update_puzzle_hud)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(Screen::Playing)),
+    );
+    app.add_systems(
+        Update,
+        judge_selected_mrna
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Why a level was failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// A base the level leaves out, which can only get in by pasting.
+    DisallowedBase(NucleobaseRna),
+    TooLong {
+        max: usize,
+    },
+    BannedCodon(Codon),
+    WrongPhrase,
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::DisallowedBase(base) => write!(f, "{} isn't allowed", base.to_char()),
+            Failure::TooLong { max } => write!(f, "longer than {max} codons"),
+            Failure::BannedCodon(codon) => write!(f, "{codon} is banned"),
+            Failure::WrongPhrase => write!(f, "it doesn't say the phrase"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Within the limits and no stop codon yet.
+    Pending,
+    Solved,
+    Failed(Failure),
+}
+
+impl Level {
+    /// The limits are checked on every codon, the phrase once any product is released.
+    pub fn judge(&self, mrna: &Mrna) -> Verdict {
+        if let Some(base) = mrna
+            .bases()
+            .into_iter()
+            .find(|b| !self.allowed_bases.contains(b))
+        {
+            return Verdict::Failed(Failure::DisallowedBase(base));
+        }
+        let codons = mrna.codons();
+        if let Some(codon) = codons.iter().find(|c| self.banned_codons.contains(c)) {
+            return Verdict::Failed(Failure::BannedCodon(*codon));
+        }
+        if let Some(max) = self.max_codons {
+            if codons.len() > max {
+                return Verdict::Failed(Failure::TooLong { max });
+            }
+        }
+        let Some(product) = self.judged_product(mrna) else {
+            return Verdict::Pending;
+        };
+        if product.text == self.target {
+            Verdict::Solved
+        } else {
+            Verdict::Failed(Failure::WrongPhrase)
+        }
+    }
+
+    /// The product [`Level::judge`] compares with the target, one reading the target if any
+    /// does, otherwise the last. An edit can finish a product anywhere, not only at the end.
+    pub fn judged_product(&self, mrna: &Mrna) -> Option<Product> {
+        let mut products = mrna.products(&self.table());
+        match products.iter().position(|p| p.text == self.target) {
+            Some(index) => Some(products.swap_remove(index)),
+            None => products.pop(),
+        }
+    }
+
+    /// What is scored and diffed, the judged product or, without one, the whole translation.
+    fn judged_text(&self, mrna: &Mrna) -> String {
+        self.judged_product(mrna)
+            .map_or_else(|| self.translate(mrna), |product| product.text)
+    }
+
+    /// Characters of the target that the judged product gets right, less penalties for
+    /// codons beyond the target's length and for time taken.
+    pub fn score(&self, mrna: &Mrna, seconds: f32) -> u32 {
+        let matched = self.compare(mrna).matches() as u32;
+        // one character per codon
+        let extra = self
+            .judged_text(mrna)
+            .chars()
+            .count()
+            .saturating_sub(self.target.chars().count()) as u32;
+        let points = matched * POINTS_PER_CHARACTER;
+        let penalty = extra * POINTS_PER_EXTRA_CODON + (seconds * POINTS_PER_SECOND) as u32;
+        points.saturating_sub(penalty)
+    }
+
+    /// What `mrna` reads in this level's code, [`Mrna::to_string_toki`] for Toki Pona.
+    pub fn translate(&self, mrna: &Mrna) -> String {
+        let table = self.table();
+        mrna.codons().iter().map(|c| table.translation(c)).collect()
+    }
+
+    /// The judged product against the target, for the diff on the result screen.
+    pub fn compare(&self, mrna: &Mrna) -> Alignment<char> {
+        let text: Vec<char> = self.judged_text(mrna).chars().collect();
+        let target: Vec<char> = self.target.chars().collect();
+        align(&text, &target, AlignmentMode::Global, &Scoring::TRANSLATED)
+    }
+
+    /// One line per limit, for the playing screen.
+    pub fn describe(&self) -> String {
+        let mut s = format!("Build {}", self.target);
+        if !self.known.is_empty() {
+            let known: String = self.known.iter().map(|b| b.to_char()).collect();
+            let reads: String = self
+                .table()
+                .translate_ambiguous_bases(&self.known)
+                .iter()
+                .map(|t| t.to_string())
+                .collect();
+            s.push_str(&format!("\nKnown: {known}, reads {reads}"));
+        }
+        if let Some(max) = self.max_codons {
+            s.push_str(&format!("\nAt most {max} codons"));
+        }
+        if !self.banned_codons.is_empty() {
+            let banned: Vec<String> = self.banned_codons.iter().map(|c| c.to_string()).collect();
+            s.push_str(&format!("\nBanned: {}", banned.join(" ")));
+        }
+        if self.allowed_bases.len() < 4 {
+            let allowed: String = self.allowed_bases.iter().map(|b| b.to_char()).collect();
+            s.push_str(&format!("\nOnly {allowed}"));
+        }
+        s
+    }
+}
+
+/// The levels of the campaign pack in play order, kept up to date as their files change.
+#[derive(Resource, Debug, Default)]
+pub struct Levels(pub Vec<Level>);
+
+/// The level being played, `None` in free play.
+#[derive(Resource, Debug, Default)]
+pub struct Puzzle {
+    pub level: Option<usize>,
+    /// [`Time::elapsed_seconds`] when the level started.
+    started: f32,
+    hints_shown: usize,
+}
+
+impl Puzzle {
+    pub fn current<'a>(&self, levels: &'a Levels) -> Option<&'a Level> {
+        self.level.and_then(|index| levels.0.get(index))
+    }
+}
+
+/// Run condition for what only free play allows.
+pub fn in_free_play(puzzle: Res<Puzzle>) -> bool {
+    puzzle.level.is_none()
+}
+
+/// How the last level went, shown on [`Screen::Result`].
+#[derive(Resource, Debug, Clone)]
+pub struct PuzzleResult {
+    pub level: usize,
+    pub failure: Option<Failure>,
+    pub codons: usize,
+    pub seconds: f32,
+    pub score: u32,
+    /// The translation, a match line and the target.
+    pub diff: [String; 3],
+}
+
+#[derive(Component)]
+struct PuzzleHud;
+
+/// Copy the pack's levels in when it or any level file is (re)loaded or fails.
+/// Nothing changes until every file has loaded or failed, so [`Puzzle::level`] keeps
+/// pointing at the same level. A file that breaks on reload keeps its last good version,
+/// one that never loaded is left out with an error.
+fn sync_levels(
+    mut pack_events: EventReader<AssetEvent<LevelPack>>,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    mut failed_events: EventReader<AssetLoadFailedEvent<Level>>,
+    asset_server: Res<AssetServer>,
+    level_pack_handles: Res<HandleMap<LevelPackKey>>,
+    level_packs: Res<Assets<LevelPack>>,
+    level_assets: Res<Assets<Level>>,
+    mut levels: ResMut<Levels>,
+) {
+    let changed =
+        pack_events.read().count() + level_events.read().count() + failed_events.read().count() > 0;
+    if !changed {
+        return;
+    }
+    let Some(pack) = level_pack_handles
+        .get(&LevelPackKey::Campaign)
+        .and_then(|handle| level_packs.get(handle))
+    else {
+        return;
+    };
+    let mut loaded = Vec::with_capacity(pack.levels.len());
+    let mut failed = Vec::new();
+    for handle in &pack.levels {
+        if let Some(level) = level_assets.get(handle) {
+            loaded.push(level.clone());
+            continue;
+        }
+        match asset_server.load_state(handle) {
+            LoadState::Failed(_) => failed.push(handle),
+            // wait for the rest, another event comes when each one is done
+            _ => return,
+        }
+    }
+    for handle in failed {
+        let path = handle
+            .path()
+            .map_or("?".to_string(), |path| path.to_string());
+        error!("level {path} failed to load and is left out of the pack");
+    }
+    if levels.0 != loaded {
+        levels.0 = loaded;
+    }
+}
+
+/// Switch to the level's code and bases, or back to the defaults in free play.
+/// The cells and their mRNAs come from [`crate::game::spawn::level::SpawnLevel`].
+fn start_puzzle(
+    time: Res<Time>,
+    mut puzzle: ResMut<Puzzle>,
+    levels: Res<Levels>,
+    mut active_table: ResMut<ActiveCodonTable>,
+    mut allowed_bases: ResMut<AllowedBases>,
+    mut log_text: ResMut<LogTextRes>,
+) {
+    puzzle.started = time.elapsed_seconds();
+    puzzle.hints_shown = 0;
+    let Some(level) = puzzle.current(&levels) else {
+        active_table.level = None;
+        *allowed_bases = AllowedBases::default();
+        return;
+    };
+    // the table itself is built by `sync_active_codon_table`
+    active_table.level = Some(level.mode);
+    *allowed_bases = AllowedBases(level.allowed_bases.clone());
+    *log_text = LogTextRes::default();
+}
+
+fn hud_text(puzzle: &Puzzle, levels: &Levels) -> Option<String> {
+    let level = puzzle.current(levels)?;
+    let mut s = format!(
+        "Level {}/{}: {}\n{}",
+        puzzle.level? + 1,
+        levels.0.len(),
+        level.name,
+        level.describe()
+    );
+    for hint in level.hints.iter().take(puzzle.hints_shown) {
+        s.push_str(&format!("\nHint: {hint}"));
+    }
+    if puzzle.hints_shown < level.hints.len() {
+        s.push_str("\nH for a hint");
+    }
+    Some(s)
+}
+
+fn spawn_puzzle_hud(mut commands: Commands, puzzle: Res<Puzzle>, levels: Res<Levels>) {
+    let Some(text) = hud_text(&puzzle, &levels) else {
+        return;
+    };
+    commands.spawn((
+        Name::new("Puzzle"),
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font_size: 24.,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(100.0),
+            ..default()
+        }),
+        PuzzleHud,
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn show_hint(keyboard_input: Res<ButtonInput<KeyCode>>, mut puzzle: ResMut<Puzzle>) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) && puzzle.level.is_some() {
+        puzzle.hints_shown += 1;
+    }
+}
+
+fn update_puzzle_hud(
+    puzzle: Res<Puzzle>,
+    levels: Res<Levels>,
+    mut hud_q: Query<&mut Text, With<PuzzleHud>>,
+) {
+    if !puzzle.is_changed() {
+        return;
+    }
+    let Some(text) = hud_text(&puzzle, &levels) else {
+        return;
+    };
+    for mut hud in &mut hud_q {
+        hud.sections[0].value = text.clone();
+    }
+}
+
+fn judge_selected_mrna(
+    mut commands: Commands,
+    time: Res<Time>,
+    puzzle: Res<Puzzle>,
+    levels: Res<Levels>,
+    mrna_q: Query<&Mrna, (With<SelectedMrna>, Changed<Mrna>)>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let (Some(index), Some(level), Ok(mrna)) =
+        (puzzle.level, puzzle.current(&levels), mrna_q.get_single())
+    else {
+        return;
+    };
+    let failure = match level.judge(mrna) {
+        Verdict::Pending => return,
+        Verdict::Solved => None,
+        Verdict::Failed(failure) => Some(failure),
+    };
+    let seconds = time.elapsed_seconds() - puzzle.started;
+    info!("level {} over: {:?}", level.name, failure);
+    commands.insert_resource(PuzzleResult {
+        level: index,
+        failure,
+        codons: mrna.codons().len(),
+        seconds,
+        score: level.score(mrna, seconds),
+        diff: level.compare(mrna).render(|c| *c),
+    });
+    next_screen.set(Screen::Result);
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[allow(dead_code)]
+    fn level() -> Level {
+        Level::parse(
+            r#"(name: "test", goal: "^Toki.", max_codons: Some(7), banned_codons: ["CGG"])"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_judge() {
+        let level = level();
+        assert_eq!(level.judge(&Mrna::new("")), Verdict::Pending);
+        assert_eq!(level.judge(&Mrna::new("AUGACU")), Verdict::Pending);
+        // a stop before any start doesn't end the level
+        assert_eq!(level.judge(&Mrna::new("UAA")), Verdict::Pending);
+        // only the product is compared, not what comes before its start codon
+        assert_eq!(
+            level.judge(&Mrna::new("UAAAUGACUCAGCGAAUCUAA")),
+            Verdict::Solved
+        );
+        // a message with nothing between its start and stop
+        assert_eq!(
+            level.judge(&Mrna::new("AUGUAA")),
+            Verdict::Failed(Failure::WrongPhrase)
+        );
+        assert_eq!(
+            level.judge(&Mrna::new("AUGACUCAGCGAAUCUAA")),
+            Verdict::Solved
+        );
+        assert_eq!(
+            level.judge(&Mrna::new("AUGACUCAGCGAAUAUAA")),
+            Verdict::Solved
+        );
+        assert_eq!(
+            level.judge(&Mrna::new("AUGACUCAGCGGAUCUAA")),
+            Verdict::Failed(Failure::BannedCodon("CGG".parse().unwrap()))
+        );
+        assert_eq!(
+            level.judge(&Mrna::new("AUGACUCAGAUCUAA")),
+            Verdict::Failed(Failure::WrongPhrase)
+        );
+        assert_eq!(
+            level.judge(&Mrna::new("GUUGUUGUUAUGACUCAGCGAAUC")),
+            Verdict::Failed(Failure::TooLong { max: 7 })
+        );
+    }
+
+    #[test]
+    fn test_judge_edit_before_stop() {
+        use crate::game::biology::{editor::EditorCursor, parse::parse_rna_bases};
+
+        let level = level();
+        let typed = Mrna::new("UAACGA");
+        assert_eq!(level.judge(&typed), Verdict::Pending);
+        // the stop was typed first, the start and phrase inserted in front of it
+        let inserted = EditorCursor::at(0)
+            .insert(&typed.bases(), &parse_rna_bases("AUGACUCAGCGAAUC").unwrap());
+        assert_eq!(level.judge(&Mrna::from_bases(&inserted)), Verdict::Solved);
+    }
+
+    #[test]
+    fn test_judge_allowed_bases() {
+        let level =
+            Level::parse(r#"(name: "test", goal: "^mi lili.", allowed_bases: "UAG")"#).unwrap();
+        assert_eq!(level.judge(&Mrna::new("AUG")), Verdict::Pending);
+        // only a paste can get a C in
+        assert_eq!(
+            level.judge(&Mrna::new("AUGCUU")),
+            Verdict::Failed(Failure::DisallowedBase(NucleobaseRna::Cytosine))
+        );
+    }
+
+    #[test]
+    fn test_judge_other_code() {
+        let level = Level::parse(r#"(name: "test", goal: "MEW.", translation: Real)"#).unwrap();
+        assert_eq!(level.judge(&Mrna::new("AUGGAAUGGUAA")), Verdict::Solved);
+        // UGA is a stop in the standard code, not W
+        assert_eq!(
+            level.judge(&Mrna::new("AUGGAAUGA")),
+            Verdict::Failed(Failure::WrongPhrase)
+        );
+    }
+
+    #[test]
+    fn test_score() {
+        let level = level();
+        let solved = Mrna::new("AUGACUCAGCGAAUCUAA");
+        assert_eq!(level.score(&solved, 0.0), 6 * POINTS_PER_CHARACTER);
+        assert_eq!(level.score(&solved, 10.0), 6 * POINTS_PER_CHARACTER - 20);
+        // "^Tki." gets five of six, "^TTTki." five less one codon too many
+        assert_eq!(
+            level.score(&Mrna::new("AUGACUCGAAUCUAA"), 0.0),
+            5 * POINTS_PER_CHARACTER
+        );
+        assert_eq!(
+            level.score(&Mrna::new("AUGACUACUACUCGAAUCUAA"), 0.0),
+            5 * POINTS_PER_CHARACTER - POINTS_PER_EXTRA_CODON
+        );
+        assert_eq!(level.score(&Mrna::new("UAA"), 1000.0), 0);
+        // codons before the start codon aren't part of the product
+        let after_junk = Mrna::new("UAAGUUAUGACUCAGCGAAUCUAA");
+        assert_eq!(level.score(&after_junk, 0.0), 6 * POINTS_PER_CHARACTER);
+        assert_eq!(level.compare(&after_junk).render(|c| *c)[0], "^Toki.");
+    }
+
+    #[test]
+    fn test_sync_levels_keeps_order() {
+        use bevy::asset::AssetPlugin;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.init_asset::<Level>();
+        app.init_asset::<LevelPack>();
+        app.init_asset_loader::<LevelLoader>();
+        let parse =
+            |name: &str| Level::parse(&format!(r#"(name: "{name}", goal: "^Toki.")"#)).unwrap();
+        let first = app
+            .world_mut()
+            .resource_mut::<Assets<Level>>()
+            .add(parse("first"));
+        let last = app
+            .world_mut()
+            .resource_mut::<Assets<Level>>()
+            .add(parse("last"));
+        // never loads, as if still loading
+        let loading = Handle::<Level>::weak_from_u128(0x5eed);
+        let pack = app
+            .world_mut()
+            .resource_mut::<Assets<LevelPack>>()
+            .add(LevelPack {
+                levels: vec![first, loading, last],
+            });
+        app.insert_resource(HandleMap::<LevelPackKey>::from([(
+            LevelPackKey::Campaign,
+            pack.clone(),
+        )]));
+        app.init_resource::<Levels>();
+        app.add_systems(Update, sync_levels);
+        app.update();
+        // "last" isn't moved into the loading level's place
+        assert!(app.world().resource::<Levels>().0.is_empty());
+
+        let missing = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<Level>("levels/missing.level.ron");
+        app.world_mut()
+            .resource_mut::<Assets<LevelPack>>()
+            .get_mut(&pack)
+            .unwrap()
+            .levels[1] = missing;
+        for _ in 0..500 {
+            app.update();
+            if !app.world().resource::<Levels>().0.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let names: Vec<String> = app
+            .world()
+            .resource::<Levels>()
+            .0
+            .iter()
+            .map(|level| level.name.clone())
+            .collect();
+        assert_eq!(names, ["first", "last"]);
+    }
+
+    #[test]
+    fn test_level_code_survives_sync() {
+        use bevy::{asset::AssetPlugin, state::app::StatesPlugin};
+
+        use crate::game::{
+            assets::CodonTableKey,
+            biology::{
+                codon_table::{sync_active_codon_table, CodonTable},
+                translation::TranslationMode,
+            },
+        };
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin));
+        app.init_asset::<CodonTable>();
+        let text = std::fs::read_to_string("assets/codes/toki_pona.codons.ron").unwrap();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<CodonTable>>()
+            .add(CodonTable::parse(&text).unwrap());
+        app.insert_resource(HandleMap::<CodonTableKey>::from([(
+            CodonTableKey::TokiPona,
+            handle,
+        )]));
+        let text = std::fs::read_to_string("assets/levels/mew.level.ron").unwrap();
+        app.insert_resource(Levels(vec![Level::parse(&text).unwrap()]));
+        app.init_resource::<Puzzle>();
+        app.init_resource::<ActiveCodonTable>();
+        app.init_resource::<AllowedBases>();
+        app.init_resource::<LogTextRes>();
+        app.init_state::<Screen>();
+        app.add_systems(OnEnter(Screen::Playing), start_puzzle);
+        app.add_systems(Update, sync_active_codon_table);
+        let table = |app: &App| app.world().resource::<ActiveCodonTable>().table.clone();
+
+        // the file's table, which has no mode
+        app.update();
+        assert_eq!(table(&app).mode(), None);
+
+        app.world_mut().resource_mut::<Puzzle>().level = Some(0);
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Playing);
+        app.update();
+        assert_eq!(table(&app).mode(), Some(TranslationMode::Real));
+        app.update();
+        assert_eq!(table(&app).mode(), Some(TranslationMode::Real));
+
+        // back to the file in free play
+        app.world_mut().resource_mut::<Puzzle>().level = None;
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Title);
+        app.update();
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Playing);
+        app.update();
+        assert_eq!(table(&app).mode(), None);
+    }
+}
@@ -0,0 +1,419 @@
+//! Puzzle levels written as RON under `assets/levels/`, so adding one needs no rebuild.
+//! A `.levels.ron` pack lists the `.level.ron` files in play order.
+//!
+//! ```ron
+//! (
+//!     name: "Hello",
+//!     // what the translation must read, start and stop included
+//!     goal: "^Toki Pona.",
+//!     // everything below is optional
+//!     start: "AUGACU",
+//!     // or a record of a FASTA file under `assets/`, by the first word of its header
+//!     // start_record: Some((file: "sequences/toki_pona.fasta", id: "toki")),
+//!     // IUPAC codes shown as a clue, N for any base
+//!     known: "AUGACUNNNCGNAUH",
+//!     allowed_bases: "UCAG",
+//!     translation: TokiPona,
+//!     max_codons: Some(11),
+//!     banned_codons: ["CGA"],
+//!     hints: ["o is CAG"],
+//!     cells: [(8.0, 0.0), (-8.0, 0.0)],
+//!     soundtrack: Gameplay,
+//! )
+//! ```
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::game::{
+    assets::SoundtrackKey,
+    biology::{
+        ambiguity::{parse_ambiguous_bases, AmbiguousBase},
+        fasta::{Fasta, FastaError},
+        parse::{parse_rna_bases, SequenceError},
+        translation::{NucleobaseRnaToAminoChar, TranslationMode},
+        Codon, Mrna, NucleobaseRna,
+    },
+};
+
+/// Where the cells go when a level doesn't say.
+pub const DEFAULT_CELLS: [Vec2; 2] = [Vec2::new(8., 0.), Vec2::new(-8., 0.)];
+
+#[derive(Asset, TypePath, Debug, Clone, PartialEq)]
+pub struct Level {
+    pub name: String,
+    /// What the translation should read, start and stop included, like `"^Toki."`.
+    pub target: String,
+    /// Already on the first cell's mRNA when the level starts.
+    pub start: Mrna,
+    /// A partly known sequence shown as a clue, empty for none.
+    pub known: Vec<AmbiguousBase>,
+    pub allowed_bases: Vec<NucleobaseRna>,
+    pub mode: TranslationMode,
+    pub max_codons: Option<usize>,
+    pub banned_codons: Vec<Codon>,
+    /// Shown one at a time on request.
+    pub hints: Vec<String>,
+    /// One cell at each position, the first one's mRNA is selected.
+    pub cells: Vec<Vec2>,
+    pub soundtrack: SoundtrackKey,
+}
+
+/// The file layout, see the module docs.
+#[derive(Deserialize, Debug)]
+struct LevelFile {
+    name: String,
+    goal: String,
+    #[serde(default)]
+    start: String,
+    #[serde(default)]
+    start_record: Option<RecordRef>,
+    #[serde(default)]
+    known: String,
+    #[serde(default = "all_bases")]
+    allowed_bases: String,
+    #[serde(default = "toki_pona")]
+    translation: TranslationMode,
+    #[serde(default)]
+    max_codons: Option<usize>,
+    #[serde(default)]
+    banned_codons: Vec<String>,
+    #[serde(default)]
+    hints: Vec<String>,
+    #[serde(default)]
+    cells: Vec<(f32, f32)>,
+    #[serde(default = "gameplay")]
+    soundtrack: SoundtrackKey,
+}
+
+/// A record of a FASTA file, by the first word of its header.
+#[derive(Deserialize, Debug)]
+struct RecordRef {
+    /// Under `assets/`, like `"sequences/toki_pona.fasta"`.
+    file: String,
+    id: String,
+}
+
+fn all_bases() -> String {
+    "UCAG".to_string()
+}
+fn toki_pona() -> TranslationMode {
+    TranslationMode::TokiPona
+}
+fn gameplay() -> SoundtrackKey {
+    SoundtrackKey::Gameplay
+}
+
+#[derive(Debug, Error)]
+pub enum LevelError {
+    #[error("{0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("bad starting sequence: {0}")]
+    Start(SequenceError),
+    #[error("give start or start_record, not both")]
+    StartTwice,
+    #[error("no record {id:?} in {file}")]
+    MissingRecord { file: String, id: String },
+    #[error("record {id:?} has ambiguous bases, a start must be definite")]
+    AmbiguousRecord { id: String },
+    #[error("bad known sequence: {0}")]
+    Known(SequenceError),
+    #[error("bad allowed bases: {0}")]
+    AllowedBases(SequenceError),
+    #[error("\"{codon}\" is not a codon: {source}")]
+    InvalidCodon {
+        codon: String,
+        source: SequenceError,
+    },
+    #[error("nothing translates to {c:?} with the allowed bases and codons")]
+    Unreachable { c: char },
+    #[error("the goal needs {length} codons but only {max} are allowed")]
+    TooShort { max: usize, length: usize },
+}
+
+impl Level {
+    /// Parse a level whose start, if it has one, is written out in the file.
+    pub fn parse(text: &str) -> Result<Self, LevelError> {
+        Self::from_file(ron::from_str(text)?, None)
+    }
+
+    /// `fasta` is the file named by `start_record`, read by the loader.
+    fn from_file(file: LevelFile, fasta: Option<&Fasta>) -> Result<Self, LevelError> {
+        let start = match &file.start_record {
+            None => file.start.parse().map_err(LevelError::Start)?,
+            Some(_) if !file.start.is_empty() => return Err(LevelError::StartTwice),
+            Some(record) => record_start(record, fasta)?,
+        };
+        let allowed_bases =
+            parse_rna_bases(&file.allowed_bases).map_err(LevelError::AllowedBases)?;
+        let banned_codons = file
+            .banned_codons
+            .iter()
+            .map(|s| {
+                s.parse().map_err(|source| LevelError::InvalidCodon {
+                    codon: s.clone(),
+                    source,
+                })
+            })
+            .collect::<Result<Vec<Codon>, _>>()?;
+        let cells = if file.cells.is_empty() {
+            DEFAULT_CELLS.to_vec()
+        } else {
+            file.cells.iter().map(|(x, y)| Vec2::new(*x, *y)).collect()
+        };
+        let level = Level {
+            name: file.name,
+            target: file.goal,
+            start,
+            known: parse_ambiguous_bases(&file.known).map_err(LevelError::Known)?,
+            allowed_bases,
+            mode: file.translation,
+            max_codons: file.max_codons,
+            banned_codons,
+            hints: file.hints,
+            cells,
+            soundtrack: file.soundtrack,
+        };
+        if let Some(c) = level.target.chars().find(|c| !level.can_write(*c)) {
+            return Err(LevelError::Unreachable { c });
+        }
+        let length = level.target.chars().count();
+        match level.max_codons {
+            Some(max) if max < length => Err(LevelError::TooShort { max, length }),
+            _ => Ok(level),
+        }
+    }
+
+    pub fn table(&self) -> NucleobaseRnaToAminoChar {
+        NucleobaseRnaToAminoChar::new(self.mode)
+    }
+
+    /// Whether some codon the player may type translates to `c`.
+    pub fn can_write(&self, c: char) -> bool {
+        let table = self.table();
+        (0..64).map(Codon::from_index).any(|codon| {
+            let bases = [codon.bases.0, codon.bases.1, codon.bases.2];
+            bases.iter().all(|b| self.allowed_bases.contains(b))
+                && !self.banned_codons.contains(&codon)
+                && table.translation(&codon) == c
+        })
+    }
+}
+
+fn record_start(record: &RecordRef, fasta: Option<&Fasta>) -> Result<Mrna, LevelError> {
+    let found = fasta
+        .and_then(|fasta| fasta.record(&record.id))
+        .ok_or_else(|| LevelError::MissingRecord {
+            file: record.file.clone(),
+            id: record.id.clone(),
+        })?;
+    let length = found.bases.len();
+    if length % 3 != 0 {
+        return Err(LevelError::Start(SequenceError::IncompleteCodon { length }));
+    }
+    found.mrna().ok_or_else(|| LevelError::AmbiguousRecord {
+        id: record.id.clone(),
+    })
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read level as utf-8: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Could not parse level: {0}")]
+    Level(#[from] LevelError),
+    #[error("Could not parse level pack: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not read the level's sequence file: {0}")]
+    ReadSequence(#[from] ReadAssetBytesError),
+    #[error("Could not parse the level's sequence file: {0}")]
+    Fasta(#[from] FastaError),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: LevelFile =
+            ron::from_str(&String::from_utf8(bytes)?).map_err(LevelError::from)?;
+        // read rather than loaded, so editing the sequence file reloads the level
+        let fasta = match &file.start_record {
+            Some(record) => {
+                let bytes = load_context.read_asset_bytes(record.file.clone()).await?;
+                Some(Fasta::parse(&String::from_utf8(bytes)?)?)
+            }
+            None => None,
+        };
+        Ok(Level::from_file(file, fasta.as_ref())?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Levels in play order.
+#[derive(Asset, TypePath, Debug)]
+pub struct LevelPack {
+    #[dependency]
+    pub levels: Vec<Handle<Level>>,
+}
+
+/// Paths relative to the pack file, like `(levels: ["hello.level.ron"])`.
+#[derive(Deserialize, Debug)]
+struct LevelPackFile {
+    levels: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct LevelPackLoader;
+
+impl AssetLoader for LevelPackLoader {
+    type Asset = LevelPack;
+    type Settings = ();
+    type Error = LevelLoaderError;
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: LevelPackFile = ron::from_str(&String::from_utf8(bytes)?)?;
+        let directory = load_context
+            .path()
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+        let levels = file
+            .levels
+            .iter()
+            .map(|path| load_context.load(directory.join(path)))
+            .collect();
+        Ok(LevelPack { levels })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["levels.ron"]
+    }
+}
+
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_defaults() {
+        let level = Level::parse(r#"(name: "x", goal: "^Toki.")"#).unwrap();
+        assert_eq!(level.target, "^Toki.");
+        assert_eq!(level.start, Mrna::default());
+        assert!(level.known.is_empty());
+        assert_eq!(level.allowed_bases.len(), 4);
+        assert_eq!(level.mode, TranslationMode::TokiPona);
+        assert_eq!(level.cells, DEFAULT_CELLS);
+        assert_eq!(level.soundtrack, SoundtrackKey::Gameplay);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", start: "AU")"#),
+            Err(LevelError::Start(SequenceError::IncompleteCodon { .. }))
+        ));
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", known: "AUGX")"#),
+            Err(LevelError::Known(SequenceError::InvalidBase { c: 'X', .. }))
+        ));
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", banned_codons: ["AUGG"])"#),
+            Err(LevelError::InvalidCodon { .. })
+        ));
+        // AUG is the only start codon
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", banned_codons: ["AUG"])"#),
+            Err(LevelError::Unreachable { c: '^' })
+        ));
+        // every T codon has a C
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", allowed_bases: "UAG")"#),
+            Err(LevelError::Unreachable { c: 'T' })
+        ));
+        assert!(matches!(
+            Level::parse(r#"(name: "x", goal: "^Toki.", max_codons: Some(5))"#),
+            Err(LevelError::TooShort { max: 5, length: 6 })
+        ));
+        assert!(matches!(
+            Level::parse(r#"(name: "x")"#),
+            Err(LevelError::Ron(_))
+        ));
+    }
+
+    #[test]
+    fn test_start_record() {
+        let fasta =
+            Fasta::parse(">toki the first word\nAUGACUCAGCGAAUC\n>n\nAUGNNN\n>short\nAUGA\n")
+                .unwrap();
+        let parse = |text: &str| Level::from_file(ron::from_str(text).unwrap(), Some(&fasta));
+        let level =
+            parse(r#"(name: "x", goal: "^Toki.", start_record: Some((file: "f", id: "toki")))"#)
+                .unwrap();
+        assert_eq!(level.start, Mrna::new("AUGACUCAGCGAAUC"));
+        assert!(matches!(
+            parse(r#"(name: "x", goal: "^Toki.", start_record: Some((file: "f", id: "pona")))"#),
+            Err(LevelError::MissingRecord { .. })
+        ));
+        assert!(matches!(
+            parse(r#"(name: "x", goal: "^Toki.", start_record: Some((file: "f", id: "n")))"#),
+            Err(LevelError::AmbiguousRecord { .. })
+        ));
+        assert!(matches!(
+            parse(r#"(name: "x", goal: "^Toki.", start_record: Some((file: "f", id: "short")))"#),
+            Err(LevelError::Start(SequenceError::IncompleteCodon {
+                length: 4
+            }))
+        ));
+        assert!(matches!(
+            parse(
+                r#"(name: "x", goal: "^Toki.", start: "AUG", start_record: Some((file: "f", id: "toki")))"#
+            ),
+            Err(LevelError::StartTwice)
+        ));
+    }
+
+    #[test]
+    fn test_level_files_parse() {
+        let pack = std::fs::read_to_string("assets/levels/campaign.levels.ron").unwrap();
+        let pack: LevelPackFile = ron::from_str(&pack).unwrap();
+        assert!(!pack.levels.is_empty());
+        for path in pack.levels {
+            let text = std::fs::read_to_string(format!("assets/levels/{path}")).unwrap();
+            let file: LevelFile = ron::from_str(&text).unwrap();
+            let fasta = file.start_record.as_ref().map(|record| {
+                let text = std::fs::read_to_string(format!("assets/{}", record.file)).unwrap();
+                Fasta::parse(&text).unwrap()
+            });
+            if let Err(e) = Level::from_file(file, fasta.as_ref()) {
+                panic!("{path}: {e}");
+            }
+        }
+    }
+}
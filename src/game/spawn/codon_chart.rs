@@ -5,7 +5,13 @@ use bevy_sprite3d::{Sprite3d, Sprite3dParams};
 
 use crate::{
     game::assets::{HandleMap, ImageKey},
-    game::cells::text::spawn_codon_gltfs,
+    game::biology::{
+        codon_table::ActiveCodonTable,
+        translation::{NucleobaseRnaToAminoChar, TranslationMode},
+        Codon,
+    },
+    game::cells::text::{respawn_codon_gltfs, spawn_codon_gltfs},
+    game::puzzle::Puzzle,
     screen::Screen,
 };
 
@@ -13,8 +19,16 @@ pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_codon_chart);
     app.observe(spawn_codon_gltfs);
     app.register_type::<CodonChart>();
+    app.add_systems(
+        Update,
+        (update_codon_chart_label, respawn_codon_gltfs).run_if(in_state(Screen::Playing)),
+    );
 }
 
+/// Describes the active genetic code next to the codon chart.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CodonChartLabel;
+
 #[derive(Event, Debug)]
 pub struct SpawnCodonChart;
 
@@ -43,4 +57,70 @@ fn spawn_codon_chart(
         RenderLayers::layer(0),
         StateScoped(Screen::Playing),
     ));
+    commands.spawn((
+        Name::new("CodonChartLabel"),
+        CodonChartLabel,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(20.0),
+            left: Val::Px(20.0),
+            ..default()
+        }),
+        StateScoped(Screen::Playing),
+    ));
+}
+
+fn update_codon_chart_label(
+    active_table: Res<ActiveCodonTable>,
+    puzzle: Res<Puzzle>,
+    mut label_q: Query<&mut Text, With<CodonChartLabel>>,
+    added_q: Query<(), Added<CodonChartLabel>>,
+) {
+    if !active_table.is_changed() && !puzzle.is_changed() && added_q.is_empty() {
+        return;
+    }
+    let text = describe_code(&active_table.table, puzzle.level.is_none());
+    for mut label in &mut label_q {
+        label.sections[0].value = text.clone();
+    }
+}
+
+/// The F2 hint is only shown in free play, where F2 works.
+fn describe_code(table: &NucleobaseRnaToAminoChar, free_play: bool) -> String {
+    let ncbi_id = table.mode().and_then(|mode| mode.ncbi_id());
+    let join = |codons: Vec<Codon>| {
+        codons
+            .iter()
+            .map(|codon| codon.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut text = match ncbi_id {
+        Some(id) => format!("{} (NCBI table {})", table.name(), id),
+        None => table.name().to_string(),
+    };
+    text.push_str(&format!("\nstart: {}", join(table.start_codons())));
+    text.push_str(&format!("\nstop: {}", join(table.stop_codons())));
+    if ncbi_id.is_some() {
+        let standard = NucleobaseRnaToAminoChar::new(TranslationMode::Real);
+        let reassigned: Vec<String> = (0..64)
+            .map(Codon::from_index)
+            .filter(|codon| table.translation(codon) != standard.translation(codon))
+            .map(|codon| format!("{} {}", codon, table.translation(&codon)))
+            .collect();
+        if !reassigned.is_empty() {
+            text.push_str(&format!("\nreassigned: {}", reassigned.join(", ")));
+        }
+    }
+    if free_play {
+        text.push_str("\n(F2 for the next code)");
+    }
+    text
 }
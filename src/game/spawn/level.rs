@@ -4,17 +4,35 @@ use bevy::prelude::*;
 
 use super::codon_chart::SpawnCodonChart;
 use super::player::SpawnPlayer;
+use crate::game::{biology::Mrna, cells::SpawnCells, puzzle::level::DEFAULT_CELLS};
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_level);
 }
 
+/// What a level is made of, a puzzle level fills this in from its file.
 #[derive(Event, Debug)]
-pub struct SpawnLevel;
+pub struct SpawnLevel {
+    pub cells: Vec<Vec2>,
+    /// Already on the first cell's mRNA.
+    pub start: Mrna,
+}
+
+impl Default for SpawnLevel {
+    fn default() -> Self {
+        SpawnLevel {
+            cells: DEFAULT_CELLS.to_vec(),
+            start: Mrna::default(),
+        }
+    }
+}
 
-fn spawn_level(_trigger: Trigger<SpawnLevel>, mut commands: Commands) {
-    // The only thing we have in our level is a player,
-    // but add things like walls etc. here.
+fn spawn_level(trigger: Trigger<SpawnLevel>, mut commands: Commands) {
+    let level = trigger.event();
     commands.trigger(SpawnPlayer);
     commands.trigger(SpawnCodonChart);
+    commands.trigger(SpawnCells {
+        positions: level.cells.clone(),
+        start: level.start.clone(),
+    });
 }
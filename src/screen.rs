@@ -1,8 +1,10 @@
 //! The game's main screen states and transitions between them.
 
 mod credits;
+mod level_select;
 mod loading;
 mod playing;
+mod result;
 mod splash;
 mod title;
 
@@ -17,7 +19,9 @@ pub(super) fn plugin(app: &mut App) {
         loading::plugin,
         title::plugin,
         credits::plugin,
+        level_select::plugin,
         playing::plugin,
+        result::plugin,
     ));
 }
 
@@ -29,5 +33,8 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    LevelSelect,
     Playing,
+    /// How a puzzle level went.
+    Result,
 }
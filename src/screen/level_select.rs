@@ -0,0 +1,62 @@
+//! Pick a puzzle level, one button per level of the campaign pack.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::puzzle::{Levels, Puzzle},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), enter_level_select);
+
+    app.register_type::<LevelSelectAction>();
+    app.add_systems(
+        Update,
+        handle_level_select_action.run_if(in_state(Screen::LevelSelect)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LevelSelectAction {
+    Play(usize),
+    Back,
+}
+
+fn enter_level_select(mut commands: Commands, levels: Res<Levels>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header("Levels");
+            if levels.0.is_empty() {
+                children.label("No levels in levels/campaign.levels.ron");
+            }
+            for (index, level) in levels.0.iter().enumerate() {
+                children
+                    .button(format!("{}. {}", index + 1, level.name))
+                    .insert(LevelSelectAction::Play(index));
+            }
+            children.button("Back").insert(LevelSelectAction::Back);
+        });
+}
+
+fn handle_level_select_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut puzzle: ResMut<Puzzle>,
+    mut button_query: InteractionQuery<&LevelSelectAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LevelSelectAction::Play(index) => {
+                    puzzle.level = Some(*index);
+                    next_screen.set(Screen::Playing);
+                }
+                LevelSelectAction::Back => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}
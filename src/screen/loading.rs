@@ -5,7 +5,7 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::assets::{CodonTableKey, HandleMap, ImageKey, LevelPackKey, SfxKey, SoundtrackKey},
     ui::prelude::*,
 };
 
@@ -31,10 +31,14 @@ fn all_assets_loaded(
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    codon_table_handles: Res<HandleMap<CodonTableKey>>,
+    level_pack_handles: Res<HandleMap<LevelPackKey>>,
 ) -> bool {
     image_handles.all_loaded(&asset_server)
         && sfx_handles.all_loaded(&asset_server)
         && soundtrack_handles.all_loaded(&asset_server)
+        && codon_table_handles.all_loaded(&asset_server)
+        && level_pack_handles.all_loaded(&asset_server)
 }
 
 fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
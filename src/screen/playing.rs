@@ -4,7 +4,10 @@ use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
 use super::Screen;
 use crate::game::{
-    assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack, spawn::level::SpawnLevel,
+    assets::SoundtrackKey,
+    audio::soundtrack::PlaySoundtrack,
+    puzzle::{Levels, Puzzle},
+    spawn::level::SpawnLevel,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -18,9 +21,20 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn enter_playing(mut commands: Commands) {
-    commands.trigger(SpawnLevel);
-    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Gameplay));
+fn enter_playing(mut commands: Commands, puzzle: Res<Puzzle>, levels: Res<Levels>) {
+    match puzzle.current(&levels) {
+        Some(level) => {
+            commands.trigger(SpawnLevel {
+                cells: level.cells.clone(),
+                start: level.start.clone(),
+            });
+            commands.trigger(PlaySoundtrack::Key(level.soundtrack));
+        }
+        None => {
+            commands.trigger(SpawnLevel::default());
+            commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Gameplay));
+        }
+    }
 }
 
 fn exit_playing(mut commands: Commands) {
@@ -0,0 +1,87 @@
+//! The screen after a puzzle level, solved or failed, with its score.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::puzzle::{Levels, Puzzle, PuzzleResult},
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Result), enter_result);
+
+    app.register_type::<ResultAction>();
+    app.add_systems(
+        Update,
+        handle_result_action.run_if(in_state(Screen::Result)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ResultAction {
+    Next,
+    Retry,
+    Levels,
+    Title,
+}
+
+fn enter_result(mut commands: Commands, result: Option<Res<PuzzleResult>>, levels: Res<Levels>) {
+    let Some(result) = result else {
+        return;
+    };
+    let name = levels
+        .0
+        .get(result.level)
+        .map_or("", |level| level.name.as_str());
+    let has_next = result.level + 1 < levels.0.len();
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Result))
+        .with_children(|children| {
+            match result.failure {
+                None => children.header("Solved!"),
+                Some(_) => children.header("Failed"),
+            };
+            children.label(format!("Level {}: {}", result.level + 1, name));
+            if let Some(failure) = result.failure {
+                children.label(failure.to_string());
+            }
+            for line in &result.diff {
+                children.label(line.clone());
+            }
+            children.label(format!(
+                "{} codons in {:.1} s",
+                result.codons, result.seconds
+            ));
+            children.label(format!("Score: {}", result.score));
+
+            if result.failure.is_none() && has_next {
+                children.button("Next").insert(ResultAction::Next);
+            }
+            children.button("Retry").insert(ResultAction::Retry);
+            children.button("Levels").insert(ResultAction::Levels);
+            children.button("Title").insert(ResultAction::Title);
+        });
+}
+
+fn handle_result_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut puzzle: ResMut<Puzzle>,
+    mut button_query: InteractionQuery<&ResultAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                ResultAction::Next => {
+                    puzzle.level = puzzle.level.map(|level| level + 1);
+                    next_screen.set(Screen::Playing);
+                }
+                ResultAction::Retry => next_screen.set(Screen::Playing),
+                ResultAction::Levels => next_screen.set(Screen::LevelSelect),
+                ResultAction::Title => next_screen.set(Screen::Title),
+            }
+        }
+    }
+}